@@ -1,24 +1,31 @@
 use std::iter::repeat;
 
-use options::{SizeFormat, TimeType};
+use options::{SizeFormat, TimeStyle, TimeType};
 
 use ansi_term::Style;
 use unicode_width::UnicodeWidthStr;
 
 
 
-#[derive(PartialEq, Debug, Copy, Clone)]
+#[derive(PartialEq, Debug, Clone)]
 pub enum Column {
     Permissions,
+    Octal,
+    InodeFlags,
     FileSize(SizeFormat),
-    Timestamp(TimeType, i64),
+    Timestamp(TimeType, TimeStyle, i64),
     Blocks,
     User,
     Group,
     HardLinks,
     Inode,
+    SecurityContext,
 
     GitStatus,
+    GitCommitHash,
+    GitCommitAuthor,
+    GitCommitDate(i64),
+    GitRepo,
 }
 
 /// Each column can pick its own **Alignment**. Usually, numbers are
@@ -47,14 +54,21 @@ impl Column {
     pub fn header(&self) -> &'static str {
         match *self {
             Column::Permissions     => "Permissions",
+            Column::Octal           => "Octal",
+            Column::InodeFlags      => "Flags",
             Column::FileSize(_)     => "Size",
-            Column::Timestamp(t, _) => t.header(),
+            Column::Timestamp(t, _, _) => t.header(),
             Column::Blocks          => "Blocks",
             Column::User            => "User",
             Column::Group           => "Group",
             Column::HardLinks       => "Links",
             Column::Inode           => "inode",
+            Column::SecurityContext => "Security Context",
             Column::GitStatus       => "Git",
+            Column::GitCommitHash   => "Commit",
+            Column::GitCommitAuthor => "Author",
+            Column::GitCommitDate(_) => "Committed",
+            Column::GitRepo         => "Repo",
         }
     }
 }
@@ -0,0 +1,198 @@
+use std::env;
+use std::fs;
+use std::io::{self, Read};
+use std::path::PathBuf;
+
+use options::Misfire;
+
+/// A **Config** holds the default options read from the user's
+/// configuration file. These get used whenever the same options aren't
+/// given on the command-line, so that commonly-used options don't need to be
+/// repeated each time exa is run.
+///
+/// The file is read from `$XDG_CONFIG_HOME/exa/config`, falling back to
+/// `~/.config/exa/config`. Each line holds one option, written the same way
+/// as on the command-line, with or without its leading dashes:
+///
+/// ```text
+/// # Always show a header and the group column
+/// --header
+/// group
+/// sort = size
+/// ```
+///
+/// Blank lines and lines beginning with `#` are ignored. A `colours` (or
+/// `colors`) line isn't an option, but holds style overrides in the same
+/// format as the `EXA_COLORS` environment variable.
+#[derive(PartialEq, Debug, Clone)]
+pub struct Config {
+
+    /// The path the configuration was read from, for error messages.
+    pub path: PathBuf,
+
+    /// The options in the file, in order.
+    pub lines: Vec<ConfigLine>,
+
+    /// The style overrides from the `colours` line, if there is one.
+    pub colours: Option<String>,
+}
+
+/// One option from the configuration file.
+#[derive(PartialEq, Debug, Clone)]
+pub struct ConfigLine {
+
+    /// The line number this option is on, counting from 1.
+    pub number: usize,
+
+    /// The option's short or long name, without any dashes.
+    pub name: String,
+
+    /// The option's argument, if it has one.
+    pub value: Option<String>,
+}
+
+impl Config {
+
+    /// Find where the configuration file should be, based on the user's
+    /// environment variables. Returns None if neither `$XDG_CONFIG_HOME` nor
+    /// the home directory is available.
+    pub fn default_path() -> Option<PathBuf> {
+        let base = match env::var("XDG_CONFIG_HOME") {
+            Ok(ref dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => match env::home_dir() {
+                Some(home) => home.join(".config"),
+                None       => return None,
+            },
+        };
+
+        Some(base.join("exa").join("config"))
+    }
+
+    /// Read the configuration file, if there is one. A missing file isn't an
+    /// error, but one that can't be read or parsed is.
+    pub fn load() -> Result<Option<Config>, Misfire> {
+        let path = match Config::default_path() {
+            Some(p) => p,
+            None    => return Ok(None),
+        };
+
+        let mut contents = String::new();
+        let result = fs::File::open(&path).and_then(|mut f| f.read_to_string(&mut contents));
+
+        match result {
+            Ok(_) => Config::parse(path, &contents).map(Some),
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(Misfire::BadConfig(path, 0, e.to_string())),
+        }
+    }
+
+    /// Parse the contents of a configuration file into its options.
+    pub fn parse(path: PathBuf, contents: &str) -> Result<Config, Misfire> {
+        let mut lines = Vec::new();
+        let mut colours = None;
+
+        for (index, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with("#") {
+                continue;
+            }
+
+            let option = line.trim_left_matches('-');
+
+            // The name and value can be separated by an equals sign or by
+            // whitespace, with any amount of whitespace around either.
+            let split = option.find(|c: char| c == '=' || c.is_whitespace());
+            let (name, value) = match split {
+                Some(pos) => {
+                    let value = option[pos ..].trim_left_matches(|c: char| c.is_whitespace())
+                                              .trim_left_matches('=')
+                                              .trim();
+                    (option[.. pos].to_string(), Some(value.to_string()))
+                },
+                None => (option.to_string(), None),
+            };
+
+            if name.is_empty() {
+                return Err(Misfire::BadConfig(path, index + 1, format!("No option name in '{}'", line)));
+            }
+            else if name == "colours" || name == "colors" {
+                match value {
+                    Some(v) => colours = Some(v),
+                    None    => return Err(Misfire::BadConfig(path, index + 1, format!("No colours given in '{}'", line))),
+                }
+
+                continue;
+            }
+
+            lines.push(ConfigLine {
+                number: index + 1,
+                name:   name,
+                value:  value,
+            });
+        }
+
+        Ok(Config { path: path, lines: lines, colours: colours })
+    }
+}
+
+impl ConfigLine {
+
+    /// The command-line arguments that this option corresponds to.
+    pub fn to_args(&self) -> Vec<String> {
+        match (self.name.len(), &self.value) {
+            (1, &Some(ref v)) => vec![ format!("-{}", self.name), v.clone() ],
+            (1, &None)        => vec![ format!("-{}", self.name) ],
+            (_, &Some(ref v)) => vec![ format!("--{}={}", self.name, v) ],
+            (_, &None)        => vec![ format!("--{}", self.name) ],
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Config, ConfigLine};
+    use options::Misfire;
+    use std::path::PathBuf;
+
+    fn parse(contents: &str) -> Result<Config, Misfire> {
+        Config::parse(PathBuf::from("config"), contents)
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(parse("\n# just a comment\n\n").unwrap().lines, vec![])
+    }
+
+    #[test]
+    fn flags() {
+        let config = parse("--header\ngroup\n-g").unwrap();
+        let args: Vec<Vec<String>> = config.lines.iter().map(|l| l.to_args()).collect();
+        assert_eq!(args, vec![ vec![ "--header".to_string() ], vec![ "--group".to_string() ], vec![ "-g".to_string() ] ])
+    }
+
+    #[test]
+    fn values() {
+        let config = parse("sort = size\n--level 2\n--time=accessed").unwrap();
+        let args: Vec<Vec<String>> = config.lines.iter().map(|l| l.to_args()).collect();
+        assert_eq!(args, vec![ vec![ "--sort=size".to_string() ], vec![ "--level=2".to_string() ], vec![ "--time=accessed".to_string() ] ])
+    }
+
+    #[test]
+    fn line_numbers() {
+        let config = parse("# comment\n\n--header").unwrap();
+        assert_eq!(config.lines, vec![ ConfigLine { number: 3, name: "header".to_string(), value: None } ])
+    }
+
+    #[test]
+    fn colours() {
+        let config = parse("--header\ncolours = di=34:da=31").unwrap();
+        assert_eq!(config.lines.len(), 1);
+        assert_eq!(config.colours, Some("di=34:da=31".to_string()))
+    }
+
+    #[test]
+    fn no_name() {
+        assert_eq!(parse("--header\n= size").unwrap_err(),
+                   Misfire::BadConfig(PathBuf::from("config"), 2, "No option name in '= size'".to_string()))
+    }
+}
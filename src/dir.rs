@@ -1,9 +1,14 @@
-use feature::Git;
-use file::{File, GREY};
+use feature::{Git, GitCache, GitCommit, GitHead, GitStatuses};
+use file::File;
+use options::FileFilter;
 
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::env::current_dir;
 use std::io;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 /// A **Dir** provides a cached list of the file paths in a directory that's
 /// being listed.
@@ -14,7 +19,13 @@ use std::path::{Path, PathBuf};
 pub struct Dir {
     contents: Vec<PathBuf>,
     path: PathBuf,
-    git: Option<Git>,
+    git: Option<Arc<Git>>,
+    git_cache: GitCache,
+
+    /// The most recent commit to have changed each file in this directory,
+    /// by file name. These only get looked up the first time one is needed,
+    /// and then all at once, as it means looking through the history.
+    git_commits: RefCell<Option<HashMap<String, GitCommit>>>,
 }
 
 impl Dir {
@@ -22,11 +33,16 @@ impl Dir {
     /// Create a new Dir object filled with all the files in the directory
     /// pointed to by the given path. Fails if the directory can't be read, or
     /// isn't actually a directory.
-    pub fn readdir(path: &Path) -> io::Result<Dir> {
+    ///
+    /// The Git repository the directory is in, if any, is taken from the
+    /// given cache, which gets passed on to any directories found inside it.
+    pub fn readdir(path: &Path, git_cache: &GitCache) -> io::Result<Dir> {
         fs::read_dir(path).map(|dir_obj| Dir {
             contents: dir_obj.map(|entry| entry.unwrap().path()).collect(),
             path: path.to_path_buf(),
-            git: Git::scan(path).ok(),
+            git: git_cache.get(path),
+            git_cache: git_cache.clone(),
+            git_commits: RefCell::new(None),
         })
     }
 
@@ -34,12 +50,13 @@ impl Dir {
     /// printing out an error if any of the Files fail to be created.
     ///
     /// Passing in `recurse` means that any directories will be scanned for
-    /// their contents, as well.
-    pub fn files(&self, recurse: bool) -> Vec<File> {
+    /// their contents, as well. Paths that the filter ignores are skipped
+    /// before they're looked at, so ignored directories never get scanned.
+    pub fn files(&self, recurse: bool, filter: &FileFilter) -> Vec<File> {
         let mut files = vec![];
 
-        for path in self.contents.iter() {
-            match File::from_path(path, Some(self), recurse) {
+        for path in self.contents.iter().filter(|p| !filter.should_skip(p, self)) {
+            match File::from_path(path, Some(self), recurse, &self.git_cache) {
                 Ok(file) => files.push(file),
                 Err(e)   => println!("{}: {}", path.display(), e),
             }
@@ -63,12 +80,99 @@ impl Dir {
         self.git.is_some()
     }
 
-    /// Get a string describing the Git status of the given file.
-    pub fn git_status(&self, path: &Path, prefix_lookup: bool) -> String {
+    /// Where the HEAD of the repository this directory is in points to, if
+    /// there's a repository.
+    pub fn git_head(&self) -> Option<&GitHead> {
+        self.git.as_ref().and_then(|g| g.head())
+    }
+
+    /// Whether the given file is ignored by Git. Files outside of a
+    /// repository are never ignored.
+    ///
+    /// The repository keeps its ignored paths as absolute ones, so a
+    /// relative path gets resolved against the current directory first.
+    pub fn is_git_ignored(&self, path: &Path) -> bool {
+        match self.git {
+            Some(ref git) => {
+                let path = match current_dir() {
+                    Err(_)  => Path::new(".").join(path),
+                    Ok(dir) => dir.join(path),
+                };
+
+                git.is_ignored(&path)
+            },
+            None => false,
+        }
+    }
+
+    /// Get the most recent commit to have changed the file with the given
+    /// name in this directory, if there's a repository and the file has
+    /// been committed.
+    pub fn git_commit(&self, name: &str) -> Option<GitCommit> {
+        let git = match self.git {
+            Some(ref git) => git,
+            None          => return None,
+        };
+
+        let mut commits = self.git_commits.borrow_mut();
+        if commits.is_none() {
+            *commits = Some(git.last_commits(&self.path));
+        }
+
+        commits.as_ref().and_then(|c| c.get(name).cloned())
+    }
+
+    /// Get the Git status of the given file, if there's a repository.
+    pub fn git_status(&self, path: &Path, prefix_lookup: bool) -> Option<GitStatuses> {
         match (&self.git, prefix_lookup) {
-            (&Some(ref git), false)  => git.status(path),
-            (&Some(ref git), true)   => git.dir_status(path),
-            (&None, _)               => GREY.paint("--").to_string(),
+            (&Some(ref git), false)  => Some(git.status(path)),
+            (&Some(ref git), true)   => Some(git.dir_status(path)),
+            (&None, _)               => None,
         }
     }
 }
+
+
+#[cfg(test)]
+#[cfg(feature="git")]
+mod test {
+    use super::Dir;
+    use feature::{Git, GitCache};
+
+    use std::cell::RefCell;
+    use std::env::current_dir;
+    use std::path::{Path, PathBuf};
+    use std::sync::Arc;
+
+    use git2;
+
+    /// A directory in a repository rooted at the current directory, where
+    /// `target` is ignored.
+    fn dir() -> Dir {
+        let cwd = current_dir().unwrap();
+        let statuses = vec![ (cwd.join("target"), git2::STATUS_IGNORED) ];
+
+        Dir {
+            contents:    Vec::new(),
+            path:        PathBuf::from("."),
+            git:         Some(Arc::new(Git::from_statuses(&cwd, statuses, Vec::new()))),
+            git_cache:   GitCache::new(),
+            git_commits: RefCell::new(None),
+        }
+    }
+
+    #[test]
+    fn relative_path_ignored() {
+        assert!(dir().is_git_ignored(Path::new("./target")))
+    }
+
+    #[test]
+    fn relative_child_ignored() {
+        assert!(dir().is_git_ignored(Path::new("./target/debug")))
+    }
+
+    #[test]
+    fn relative_path_not_ignored() {
+        assert!(!dir().is_git_ignored(Path::new("./src")))
+    }
+}
@@ -0,0 +1,87 @@
+//! Birth time support for Linux, which only `statx` can read.
+extern crate libc;
+
+use std::mem::zeroed;
+use std::path::Path;
+use self::libc::{c_char, c_int, c_long, c_uint};
+
+extern "C" {
+    fn syscall(number: c_long, ...) -> c_long;
+}
+
+// The C library might be too old to have a wrapper for statx, so it gets
+// called through its system call number instead, which differs between
+// architectures.
+
+#[cfg(target_arch = "x86_64")]  static SYS_STATX: c_long = 332;
+#[cfg(target_arch = "x86")]     static SYS_STATX: c_long = 383;
+#[cfg(target_arch = "aarch64")] static SYS_STATX: c_long = 291;
+#[cfg(target_arch = "arm")]     static SYS_STATX: c_long = 397;
+
+static AT_FDCWD: c_int = -100;
+static AT_SYMLINK_NOFOLLOW: c_int = 0x100;
+static STATX_BTIME: c_uint = 0x800;
+
+#[repr(C)]
+#[allow(non_camel_case_types, dead_code)]
+struct statx_timestamp {
+    tv_sec:  i64,
+    tv_nsec: u32,
+    __reserved: i32,
+}
+
+#[repr(C)]
+#[allow(non_camel_case_types, dead_code)]
+struct statx {
+    stx_mask:    u32,
+    stx_blksize: u32,
+    stx_attributes: u64,
+    stx_nlink:   u32,
+    stx_uid:     u32,
+    stx_gid:     u32,
+    stx_mode:    u16,
+    __spare0:    u16,
+    stx_ino:     u64,
+    stx_size:    u64,
+    stx_blocks:  u64,
+    stx_attributes_mask: u64,
+    stx_atime:   statx_timestamp,
+    stx_btime:   statx_timestamp,
+    stx_ctime:   statx_timestamp,
+    stx_mtime:   statx_timestamp,
+    stx_rdev_major: u32,
+    stx_rdev_minor: u32,
+    stx_dev_major:  u32,
+    stx_dev_minor:  u32,
+    __spare2:    [u64; 14],
+}
+
+/// The time the file at the given path was created, as seconds since the
+/// Unix epoch and nanoseconds past that, without following symlinks.
+/// Returns None if the kernel is too old to have statx, or the filesystem
+/// doesn't record birth times.
+#[cfg(any(target_arch = "x86_64", target_arch = "x86", target_arch = "aarch64", target_arch = "arm"))]
+pub fn birth_time(path: &Path) -> Option<(i64, i64)> {
+    let c_path = match path.as_os_str().to_cstring() {
+        Some(cstring) => cstring,
+        None => return None,
+    };
+
+    let mut buf: statx = unsafe { zeroed() };
+    let result = unsafe {
+        syscall(SYS_STATX, AT_FDCWD, c_path.as_ptr() as *const c_char,
+                AT_SYMLINK_NOFOLLOW, STATX_BTIME, &mut buf as *mut statx)
+    };
+
+    if result == 0 && buf.stx_mask & STATX_BTIME != 0 {
+        Some((buf.stx_btime.tv_sec, buf.stx_btime.tv_nsec as i64))
+    }
+    else {
+        None
+    }
+}
+
+#[cfg(not(any(target_arch = "x86_64", target_arch = "x86", target_arch = "aarch64", target_arch = "arm")))]
+pub fn birth_time(_: &Path) -> Option<(i64, i64)> {
+    None
+}
@@ -1,78 +1,742 @@
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 
-use ansi_term::{ANSIString, ANSIStrings};
-use ansi_term::Colour::*;
 use git2;
 
-use file::GREY;
+use super::{GitCommit, GitHead, GitRepo, GitStatus, GitStatuses};
+
+/// A **GitCache** holds every repository that has been scanned during this
+/// run, by the path of its working directory. It gets shared between every
+/// directory that gets listed, so a recursive listing of a repository only
+/// has to scan it once, rather than once for each directory inside it.
+///
+/// Cloning a cache gives another handle to the same repositories, so it can
+/// be passed to other threads.
+#[derive(Clone)]
+pub struct GitCache {
+    repos: Arc<Mutex<HashMap<PathBuf, Arc<Git>>>>,
+
+    /// The revision to compare each repository's files against, instead of
+    /// its HEAD, if one was given.
+    rev: Option<String>,
+}
+
+impl GitCache {
+
+    /// Create a new cache, with no repositories in it yet.
+    pub fn new() -> GitCache {
+        GitCache::with_ref(None)
+    }
+
+    /// Create a new cache whose repositories get their files' statuses
+    /// compared against the given revision, such as `main` or `HEAD~5`.
+    pub fn with_ref(rev: Option<String>) -> GitCache {
+        GitCache { repos: Arc::new(Mutex::new(HashMap::new())), rev: rev }
+    }
+
+    /// Get the repository on or above the given directory, scanning it if
+    /// it hasn't been scanned already. Returns None if the directory isn't
+    /// in a repository, or the repository couldn't be scanned, which
+    /// includes when the revision to compare against isn't in it.
+    ///
+    /// The repository still has to be discovered each time, as the
+    /// directory could be in a different repository nested inside one
+    /// that's already been scanned, such as a submodule. This is cheap
+    /// compared to scanning it.
+    pub fn get(&self, path: &Path) -> Option<Arc<Git>> {
+        let repo = match git2::Repository::discover(path) {
+            Ok(r)  => r,
+            Err(_) => return None,
+        };
+
+        let key = repo.workdir().unwrap_or(repo.path()).to_path_buf();
+
+        // The lock is held while scanning, so two threads listing the same
+        // repository at once don't both scan it.
+        let mut repos = self.repos.lock().unwrap();
+        if let Some(git) = repos.get(&key) {
+            return Some(git.clone());
+        }
+
+        match Git::scan(&repo, self.rev.as_ref().map(|r| &r[..])) {
+            Ok(git) => {
+                let git = Arc::new(git);
+                repos.insert(key, git.clone());
+                Some(git)
+            },
+            Err(_) => None,
+        }
+    }
+
+    /// Whether the given revision can be found in the repository on or
+    /// above the given path. A path that isn't in a repository has nothing
+    /// to compare against, so any revision does for it.
+    pub fn has_rev(path: &Path, rev: &str) -> bool {
+        match git2::Repository::discover(path) {
+            Ok(repo) => repo.revparse_single(rev).and_then(|o| o.peel(git2::ObjectType::Tree)).is_ok(),
+            Err(_)   => true,
+        }
+    }
+}
+
+impl GitRepo {
+
+    /// Find out whether the directory at the given path is the root of a
+    /// repository's working directory, and if so, where its HEAD is and
+    /// whether it has any uncommitted changes. Untracked files count as
+    /// changes, but ignored ones don't.
+    pub fn at(path: &Path) -> Option<GitRepo> {
+        let dot_git = match fs::symlink_metadata(path.join(".git")) {
+            Ok(m)  => m,
+            Err(_) => return None,
+        };
+
+        let repo = match git2::Repository::open(path) {
+            Ok(r)  => r,
+            Err(_) => return None,
+        };
+
+        let mut options = git2::StatusOptions::new();
+        options.include_untracked(true)
+               .include_ignored(false);
+
+        let dirty = repo.statuses(Some(&mut options)).map(|s| s.len() > 0).unwrap_or(false);
+
+        Some(GitRepo {
+            submodule: dot_git.is_file() && is_submodule_dir(repo.path()),
+            head:      Git::scan_head(&repo, dirty),
+        })
+    }
+}
 
 /// Container of Git statuses for all the files in this folder's Git repository.
+///
+/// The statuses are indexed by path, so that looking up a file's status
+/// doesn't mean searching through every change in the repository. The
+/// combined statuses of directories are worked out once, when the
+/// repository is scanned, rather than each time a directory gets listed.
 pub struct Git {
-    statuses: Vec<(PathBuf, git2::Status)>,
+
+    /// The path to the repository's `.git` directory, so it can be opened
+    /// again to look through its history.
+    git_dir: PathBuf,
+
+    /// The path to the repository's working directory, which is None for
+    /// bare repositories.
+    workdir: Option<PathBuf>,
+
+    /// The status of each file that has one, by its full path.
+    statuses: HashMap<PathBuf, git2::Status>,
+
+    /// The combined status of every file beneath each directory that
+    /// contains a changed file, by the directory's full path.
+    dir_statuses: HashMap<PathBuf, git2::Status>,
+
+    /// The full paths of files and directories that Git ignores.
+    ignored: HashSet<PathBuf>,
+
+    /// The full paths of files with merge conflicts, along with every
+    /// directory that contains one.
+    conflicted: HashSet<PathBuf>,
+
+    /// Where the repository's HEAD is, if it points to a commit.
+    head: Option<GitHead>,
 }
 
 impl Git {
 
-    /// Discover a Git repository on or above this directory, scanning it for
-    /// the files' statuses if one is found.
-    pub fn scan(path: &Path) -> Result<Git, git2::Error> {
-        let repo = try!(git2::Repository::discover(path));
+    /// Scan a repository for the statuses of its files. If a revision is
+    /// given, then the index statuses compare the index against that
+    /// revision's tree, rather than against HEAD.
+    fn scan(repo: &git2::Repository, rev: Option<&str>) -> Result<Git, git2::Error> {
         let workdir = match repo.workdir() {
             Some(w) => w,
-            None => return Ok(Git { statuses: vec![] }),  // bare repo
+            None => {
+                // bare repo
+                let mut git = Git::from_statuses(repo.path(), Vec::new(), Vec::new());
+                git.git_dir = repo.path().to_path_buf();
+                return Ok(git);
+            },
         };
 
-        let statuses = try!(repo.statuses(None)).iter()
+        // Ignored files are included so they can be hidden, but ignored
+        // directories aren't descended into, as they can be huge.
+        let mut options = git2::StatusOptions::new();
+        options.include_untracked(true)
+               .recurse_untracked_dirs(true)
+               .include_ignored(true)
+               .recurse_ignored_dirs(false);
+
+        let statuses: Vec<(PathBuf, git2::Status)> = try!(repo.statuses(Some(&mut options))).iter()
                                                 .map(|e| (workdir.join(Path::new(e.path().unwrap())), e.status()))
                                                 .collect();
 
-        Ok(Git { statuses: statuses })
+        // Whether the working tree is dirty is always about HEAD, so it has
+        // to be worked out before the statuses get compared against any
+        // other revision.
+        let dirty = statuses.iter().any(|&(_, status)| {
+            let mut changes = status;
+            changes.remove(git2::STATUS_IGNORED);
+            !changes.is_empty()
+        });
+
+        let statuses = match rev {
+            Some(rev) => try!(Git::compare_to_rev(repo, workdir, rev, statuses)),
+            None      => statuses,
+        };
+
+        // A file with a merge conflict has more than one entry in the index,
+        // each with a non-zero stage number in bits 12 and 13 of its flags.
+        let conflicts = try!(repo.index()).iter()
+                                          .filter(|e| (e.flags >> 12) & 3 != 0)
+                                          .map(|e| workdir.join(&*String::from_utf8_lossy(&e.path)))
+                                          .collect();
+
+        let mut git = Git::from_statuses(workdir, statuses, conflicts);
+        git.git_dir = repo.path().to_path_buf();
+        git.workdir = Some(workdir.to_path_buf());
+
+        git.head = Git::scan_head(repo, dirty);
+
+        Ok(git)
     }
 
-    /// Get the status for the file at the given path, if present.
-    pub fn status(&self, path: &Path) -> String {
-        let status = self.statuses.iter()
-                                  .find(|p| p.0.as_path() == path);
-        match status {
-            Some(&(_, s)) => ANSIStrings( &[Git::index_status(s), Git::working_tree_status(s) ]).to_string(),
-            None => GREY.paint("--").to_string(),
+    /// Replace the index statuses in a list, which compare the index against
+    /// HEAD, with ones comparing it against the tree of the given revision.
+    /// The working tree statuses are left alone. Files that end up with no
+    /// status at all, because they're the same as in the revision, get
+    /// removed from the list.
+    fn compare_to_rev(repo: &git2::Repository, workdir: &Path, rev: &str, list: Vec<(PathBuf, git2::Status)>) -> Result<Vec<(PathBuf, git2::Status)>, git2::Error> {
+        let index_statuses = git2::STATUS_INDEX_NEW | git2::STATUS_INDEX_MODIFIED | git2::STATUS_INDEX_DELETED
+                           | git2::STATUS_INDEX_RENAMED | git2::STATUS_INDEX_TYPECHANGE;
+
+        let mut statuses: HashMap<PathBuf, git2::Status> = list.into_iter().map(|(path, mut status)| {
+            status.remove(index_statuses);
+            (path, status)
+        }).collect();
+
+        let tree = try!(try!(repo.revparse_single(rev)).peel(git2::ObjectType::Tree));
+        let diff = try!(repo.diff_tree_to_index(tree.as_tree(), None, None));
+
+        for delta in diff.deltas() {
+            let status = match delta.status() {
+                git2::Delta::Added      => git2::STATUS_INDEX_NEW,
+                git2::Delta::Modified   => git2::STATUS_INDEX_MODIFIED,
+                git2::Delta::Deleted    => git2::STATUS_INDEX_DELETED,
+                git2::Delta::Renamed    => git2::STATUS_INDEX_RENAMED,
+                git2::Delta::Typechange => git2::STATUS_INDEX_TYPECHANGE,
+                _                       => continue,
+            };
+
+            let path = match delta.new_file().path().or(delta.old_file().path()) {
+                Some(p) => workdir.join(p),
+                None    => continue,
+            };
+
+            let entry = statuses.entry(path).or_insert(git2::Status::empty());
+            *entry = *entry | status;
+        }
+
+        Ok(statuses.into_iter().filter(|&(_, s)| !s.is_empty()).collect())
+    }
+
+    /// Find the branch or commit that HEAD points to, and how far it is from
+    /// its upstream branch. Returns None for a new repository with no
+    /// commits yet.
+    fn scan_head(repo: &git2::Repository, dirty: bool) -> Option<GitHead> {
+        let head = match repo.head() {
+            Ok(h)  => h,
+            Err(_) => return None,
+        };
+
+        let oid = match head.target() {
+            Some(o) => o,
+            None    => return None,
+        };
+
+        if repo.head_detached().unwrap_or(false) {
+            let mut hash = oid.to_string();
+            hash.truncate(7);
+            return Some(GitHead { name: hash, detached: true, upstream: None, dirty: dirty });
+        }
+
+        let name = head.shorthand().unwrap_or("HEAD").to_string();
+        let upstream = git2::Branch::wrap(head).upstream().ok()
+                                               .and_then(|u| u.get().target())
+                                               .and_then(|u| repo.graph_ahead_behind(oid, u).ok());
+
+        Some(GitHead { name: name, detached: false, upstream: upstream, dirty: dirty })
+    }
+
+    /// Index a list of statuses, adding each one to the combined statuses of
+    /// the directories above it, up to and including the working directory.
+    /// Conflicted files get added to the directories above them, too.
+    ///
+    /// A directory isn't ignored just because it contains an ignored file,
+    /// so ignored statuses only count for the paths they're for.
+    pub fn from_statuses(workdir: &Path, list: Vec<(PathBuf, git2::Status)>, conflicts: Vec<PathBuf>) -> Git {
+        let mut statuses = HashMap::with_capacity(list.len());
+        let mut dir_statuses: HashMap<PathBuf, git2::Status> = HashMap::new();
+        let mut ignored = HashSet::new();
+        let mut conflicted = HashSet::new();
+
+        for (path, status) in list.into_iter() {
+            if status.contains(git2::STATUS_IGNORED) {
+                ignored.insert(path.clone());
+            }
+
+            let mut parent_status = status;
+            parent_status.remove(git2::STATUS_IGNORED);
+
+            let mut current = Some(path.as_path());
+            while let Some(p) = current {
+                let combined = dir_statuses.entry(p.to_path_buf()).or_insert(git2::Status::empty());
+                *combined = *combined | if p == path.as_path() { status } else { parent_status };
+
+                if p == workdir { break; }
+                current = p.parent();
+            }
+
+            statuses.insert(path, status);
+        }
+
+        for path in conflicts.iter() {
+            let mut current = Some(path.as_path());
+            while let Some(p) = current {
+                conflicted.insert(p.to_path_buf());
+
+                if p == workdir { break; }
+                current = p.parent();
+            }
+        }
+
+        Git {
+            git_dir:      PathBuf::new(),
+            workdir:      None,
+            statuses:     statuses,
+            dir_statuses: dir_statuses,
+            ignored:      ignored,
+            conflicted:   conflicted,
+            head:         None,
         }
     }
 
+    /// Get the status for the file at the given path, if present.
+    pub fn status(&self, path: &Path) -> GitStatuses {
+        let s = self.statuses.get(path).map(|&s| s).unwrap_or(git2::Status::empty());
+        self.statuses_for(path, s)
+    }
+
     /// Get the combined status for all the files whose paths begin with the
     /// path that gets passed in. This is used for getting the status of
     /// directories, which don't really have an 'official' status.
-    pub fn dir_status(&self, dir: &Path) -> String {
-        let s = self.statuses.iter()
-                             .filter(|p| p.0.starts_with(dir))
-                             .fold(git2::Status::empty(), |a, b| a | b.1);
+    pub fn dir_status(&self, dir: &Path) -> GitStatuses {
+        let s = self.dir_statuses.get(dir).map(|&s| s).unwrap_or(git2::Status::empty());
+        self.statuses_for(dir, s)
+    }
+
+    /// Turn libgit2's status flags for a path into the statuses for the
+    /// index and the working tree. Merge conflicts show up in both, as they
+    /// need to be resolved before anything can be committed. Files inside
+    /// an ignored directory don't have statuses of their own, so they're
+    /// checked for separately.
+    fn statuses_for(&self, path: &Path, status: git2::Status) -> GitStatuses {
+        if self.conflicted.contains(path) {
+            GitStatuses { staged: GitStatus::Conflicted, unstaged: GitStatus::Conflicted }
+        }
+        else if status.contains(git2::STATUS_IGNORED) || (status.is_empty() && self.is_ignored(path)) {
+            GitStatuses { staged: GitStatus::NotModified, unstaged: GitStatus::Ignored }
+        }
+        else {
+            GitStatuses { staged: Git::index_status(status), unstaged: Git::working_tree_status(status) }
+        }
+    }
+
+    /// Where this repository's HEAD is, if it points to a commit.
+    pub fn head(&self) -> Option<&GitHead> {
+        self.head.as_ref()
+    }
+
+    /// Find the most recent commit to have changed each of the files in the
+    /// given directory, by the files' names.
+    ///
+    /// This walks back through the history from HEAD once for the whole
+    /// directory, comparing each commit's version of the directory against
+    /// its parents', and stops as soon as every file has been found. A file
+    /// counts as changed by a commit if it's different from the version in
+    /// every one of its parents, which is how `git log` decides. Files that
+    /// haven't been committed aren't included.
+    pub fn last_commits(&self, dir: &Path) -> HashMap<String, GitCommit> {
+        let mut commits = HashMap::new();
+
+        // If something goes wrong part of the way through, then the
+        // commits found so far are still correct.
+        let _ = self.walk_commits(dir, &mut commits);
+        commits
+    }
+
+    fn walk_commits(&self, dir: &Path, commits: &mut HashMap<String, GitCommit>) -> Result<(), git2::Error> {
+        let relative = match self.relative_path(dir) {
+            Some(r) => r,
+            None    => return Ok(()),
+        };
+
+        let repo = try!(git2::Repository::open(&self.git_dir));
+        let head = match try!(repo.head()).target() {
+            Some(oid) => oid,
+            None      => return Ok(()),
+        };
+
+        let wanted = entries_at(&repo, &try!(repo.find_commit(head)), &relative);
 
-        ANSIStrings( &[Git::index_status(s), Git::working_tree_status(s)] ).to_string()
+        let mut revwalk = try!(repo.revwalk());
+        revwalk.set_sorting(git2::SORT_TIME);
+        try!(revwalk.push(head));
+
+        for oid in revwalk {
+            if commits.len() == wanted.len() {
+                break;
+            }
+
+            let commit = try!(repo.find_commit(oid));
+            let parents: Vec<HashMap<String, git2::Oid>> = commit.parents().map(|p| entries_at(&repo, &p, &relative)).collect();
+
+            for (name, id) in entries_at(&repo, &commit, &relative).into_iter() {
+                if commits.contains_key(&name) || !wanted.contains_key(&name) {
+                    continue;
+                }
+
+                let unchanged = parents.iter().any(|p| p.get(&name) == Some(&id));
+                if !unchanged {
+                    let mut hash = commit.id().to_string();
+                    hash.truncate(7);
+
+                    let author = commit.author().name().unwrap_or("").to_string();
+                    commits.insert(name, GitCommit { hash: hash, author: author, time: commit.time().seconds() });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The path to the given directory from the root of the working
+    /// directory, or None if it's not inside it.
+    fn relative_path(&self, dir: &Path) -> Option<PathBuf> {
+        let workdir = match self.workdir {
+            Some(ref w) => w,
+            None        => return None,
+        };
+
+        let dir = match env::current_dir() {
+            Ok(cwd) => cwd.join(dir),
+            Err(_)  => return None,
+        };
+
+        if dir.starts_with(workdir) {
+            Some(dir.components().skip(workdir.components().count()).map(|c| c.as_os_str()).collect())
+        }
+        else {
+            None
+        }
+    }
+
+    /// Whether the file at the given path is ignored by Git, either because
+    /// it matches an ignore rule itself or because one of its parent
+    /// directories does.
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        let mut current = Some(path);
+        while let Some(p) = current {
+            if self.ignored.contains(p) {
+                return true;
+            }
+
+            current = p.parent();
+        }
+
+        false
     }
 
-    /// The character to display if the file has been modified, but not staged.
-    fn working_tree_status(status: git2::Status) -> ANSIString<'static> {
+    /// The status of the file if it has been modified, but not staged.
+    fn working_tree_status(status: git2::Status) -> GitStatus {
         match status {
-            s if s.contains(git2::STATUS_WT_NEW) => Green.paint("A"),
-            s if s.contains(git2::STATUS_WT_MODIFIED) => Blue.paint("M"),
-            s if s.contains(git2::STATUS_WT_DELETED) => Red.paint("D"),
-            s if s.contains(git2::STATUS_WT_RENAMED) => Yellow.paint("R"),
-            s if s.contains(git2::STATUS_WT_TYPECHANGE) => Purple.paint("T"),
-            _ => GREY.paint("-"),
+            s if s.contains(git2::STATUS_WT_NEW) => GitStatus::New,
+            s if s.contains(git2::STATUS_WT_MODIFIED) => GitStatus::Modified,
+            s if s.contains(git2::STATUS_WT_DELETED) => GitStatus::Deleted,
+            s if s.contains(git2::STATUS_WT_RENAMED) => GitStatus::Renamed,
+            s if s.contains(git2::STATUS_WT_TYPECHANGE) => GitStatus::TypeChange,
+            _ => GitStatus::NotModified,
         }
     }
 
-    /// The character to display if the file has been modified, and the change
-    /// has been staged.
-    fn index_status(status: git2::Status) -> ANSIString<'static> {
+    /// The status of the file if it has been modified, and the change has
+    /// been staged.
+    fn index_status(status: git2::Status) -> GitStatus {
         match status {
-            s if s.contains(git2::STATUS_INDEX_NEW) => Green.paint("A"),
-            s if s.contains(git2::STATUS_INDEX_MODIFIED) => Blue.paint("M"),
-            s if s.contains(git2::STATUS_INDEX_DELETED) => Red.paint("D"),
-            s if s.contains(git2::STATUS_INDEX_RENAMED) => Yellow.paint("R"),
-            s if s.contains(git2::STATUS_INDEX_TYPECHANGE) => Purple.paint("T"),
-            _ => GREY.paint("-"),
+            s if s.contains(git2::STATUS_INDEX_NEW) => GitStatus::New,
+            s if s.contains(git2::STATUS_INDEX_MODIFIED) => GitStatus::Modified,
+            s if s.contains(git2::STATUS_INDEX_DELETED) => GitStatus::Deleted,
+            s if s.contains(git2::STATUS_INDEX_RENAMED) => GitStatus::Renamed,
+            s if s.contains(git2::STATUS_INDEX_TYPECHANGE) => GitStatus::TypeChange,
+            _ => GitStatus::NotModified,
         }
     }
 }
 
+/// The names and object IDs of the entries in a commit's version of the
+/// directory at the given path, which are empty if the directory didn't
+/// exist then.
+fn entries_at(repo: &git2::Repository, commit: &git2::Commit, relative: &Path) -> HashMap<String, git2::Oid> {
+    let root = match commit.tree() {
+        Ok(t)  => t,
+        Err(_) => return HashMap::new(),
+    };
+
+    let tree = if relative.components().next().is_none() {
+        root
+    }
+    else {
+        match root.get_path(relative).and_then(|e| repo.find_tree(e.id())) {
+            Ok(t)  => t,
+            Err(_) => return HashMap::new(),
+        }
+    };
+
+    tree.iter().filter_map(|e| e.name().map(|n| (n.to_string(), e.id()))).collect()
+}
+
+/// Whether the given `.git` directory belongs to a submodule. A submodule's
+/// repository is kept in its superproject's `.git/modules` directory, with
+/// only a `.git` file in its working directory pointing to it. Linked
+/// worktrees have `.git` files too, but theirs point into `.git/worktrees`.
+fn is_submodule_dir(git_dir: &Path) -> bool {
+    let names: Vec<_> = git_dir.components().map(|c| c.as_os_str()).collect();
+    names.windows(2).any(|w| w[0] == ".git" && w[1] == "modules")
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Git, is_submodule_dir};
+    use feature::{GitStatus, GitStatuses};
+
+    use std::env::temp_dir;
+    use std::fs;
+    use std::io::Write;
+    use std::path::{Path, PathBuf};
+
+    use git2;
+    use test::Bencher;
+
+    /// A repository with a thousand changed files in each of ten
+    /// directories, and a few ignored ones.
+    fn statuses() -> Vec<(PathBuf, git2::Status)> {
+        let mut list = Vec::new();
+
+        for d in 0 .. 10 {
+            for f in 0 .. 1000 {
+                let path = PathBuf::from(format!("/repo/dir{}/file{}", d, f));
+                let status = if f % 2 == 0 { git2::STATUS_WT_MODIFIED } else { git2::STATUS_INDEX_NEW };
+                list.push((path, status));
+            }
+        }
+
+        list.push((PathBuf::from("/repo/target"), git2::STATUS_IGNORED));
+        list
+    }
+
+    fn git() -> Git {
+        let conflicts = vec![ PathBuf::from("/repo/dir5/file3") ];
+        Git::from_statuses(Path::new("/repo"), statuses(), conflicts)
+    }
+
+    #[test]
+    fn file_status() {
+        let status = git().status(Path::new("/repo/dir3/file10"));
+        assert_eq!(status, GitStatuses { staged: GitStatus::NotModified, unstaged: GitStatus::Modified })
+    }
+
+    #[test]
+    fn unchanged_file_status() {
+        let status = git().status(Path::new("/repo/dir3/unchanged"));
+        assert_eq!(status, GitStatuses { staged: GitStatus::NotModified, unstaged: GitStatus::NotModified })
+    }
+
+    #[test]
+    fn dir_status() {
+        let status = git().dir_status(Path::new("/repo/dir3"));
+        assert_eq!(status, GitStatuses { staged: GitStatus::New, unstaged: GitStatus::Modified })
+    }
+
+    #[test]
+    fn workdir_status() {
+        let status = git().dir_status(Path::new("/repo"));
+        assert_eq!(status, GitStatuses { staged: GitStatus::New, unstaged: GitStatus::Modified })
+    }
+
+    #[test]
+    fn ignored() {
+        let git = git();
+        assert!(git.is_ignored(Path::new("/repo/target")));
+        assert!(git.is_ignored(Path::new("/repo/target/debug/exa")));
+        assert!(!git.is_ignored(Path::new("/repo/dir3/file10")))
+    }
+
+    #[test]
+    fn ignored_status() {
+        let status = git().status(Path::new("/repo/target/debug"));
+        assert_eq!(status, GitStatuses { staged: GitStatus::NotModified, unstaged: GitStatus::Ignored })
+    }
+
+    #[test]
+    fn ignored_file_does_not_ignore_dir() {
+        let status = git().dir_status(Path::new("/repo"));
+        assert!(status.unstaged != GitStatus::Ignored)
+    }
+
+    #[test]
+    fn conflicted_status() {
+        let conflicted = GitStatuses { staged: GitStatus::Conflicted, unstaged: GitStatus::Conflicted };
+        assert_eq!(git().status(Path::new("/repo/dir5/file3")), conflicted);
+        assert_eq!(git().dir_status(Path::new("/repo/dir5")), conflicted)
+    }
+
+    #[test]
+    fn submodule_dir() {
+        assert!(is_submodule_dir(Path::new("/repo/.git/modules/lib/")));
+    }
+
+    #[test]
+    fn nested_submodule_dir() {
+        assert!(is_submodule_dir(Path::new("/repo/.git/modules/lib/modules/inner/")));
+    }
+
+    #[test]
+    fn worktree_dir() {
+        assert!(!is_submodule_dir(Path::new("/repo/.git/worktrees/feature/")));
+    }
+
+    #[test]
+    fn repo_in_modules_dir() {
+        assert!(!is_submodule_dir(Path::new("/home/modules/repo/.git/")));
+    }
+
+    /// Make an empty repository to test with, named after the test.
+    fn test_repo(name: &str) -> git2::Repository {
+        let path = temp_dir().join(format!("exa-test-{}", name));
+        let _ = fs::remove_dir_all(&path);
+        git2::Repository::init(&path).unwrap()
+    }
+
+    /// Write the given files into the repository's working directory, and
+    /// commit them on top of HEAD at the given time.
+    fn commit(repo: &git2::Repository, files: &[(&str, &str)], time: i64) -> String {
+        let workdir = repo.workdir().unwrap().to_path_buf();
+        let mut index = repo.index().unwrap();
+
+        for &(name, contents) in files.iter() {
+            let path = workdir.join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::File::create(&path).unwrap().write_all(contents.as_bytes()).unwrap();
+            index.add_path(Path::new(name)).unwrap();
+        }
+
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = git2::Signature::new("Tester", "tester@example.com", &git2::Time::new(time, 0)).unwrap();
+
+        let parents = match repo.head() {
+            Ok(head) => vec![ repo.find_commit(head.target().unwrap()).unwrap() ],
+            Err(_)   => Vec::new(),
+        };
+
+        let parents: Vec<&git2::Commit> = parents.iter().collect();
+        let oid = repo.commit(Some("HEAD"), &signature, &signature, "commit", &tree, &parents).unwrap();
+
+        let mut hash = oid.to_string();
+        hash.truncate(7);
+        hash
+    }
+
+    /// A repository where `a` and `b` were changed after the first commit,
+    /// but nothing in `sub` was, along with the hashes of the commits.
+    fn history(name: &str) -> (git2::Repository, Vec<String>) {
+        let repo = test_repo(name);
+        let first  = commit(&repo, &[ ("a", "one"), ("b", "one"), ("sub/c", "one") ], 1000);
+        let second = commit(&repo, &[ ("a", "two") ], 2000);
+        let third  = commit(&repo, &[ ("b", "three") ], 3000);
+        (repo, vec![ first, second, third ])
+    }
+
+    #[test]
+    fn last_commits_newest() {
+        let (repo, hashes) = history("last-commits-newest");
+        let workdir = repo.workdir().unwrap().to_path_buf();
+        let commits = Git::scan(&repo, None).unwrap().last_commits(&workdir);
+
+        assert_eq!(commits["a"].hash, hashes[1]);
+        assert_eq!(commits["a"].time, 2000);
+        assert_eq!(commits["b"].hash, hashes[2]);
+        assert_eq!(commits["b"].author, "Tester");
+
+        fs::remove_dir_all(&workdir).unwrap();
+    }
+
+    #[test]
+    fn last_commits_untouched_directory() {
+        let (repo, hashes) = history("last-commits-untouched");
+        let workdir = repo.workdir().unwrap().to_path_buf();
+        let git = Git::scan(&repo, None).unwrap();
+
+        // The directory itself was last changed by the first commit, and
+        // so was everything inside it.
+        assert_eq!(git.last_commits(&workdir)["sub"].hash, hashes[0]);
+        assert_eq!(git.last_commits(&workdir.join("sub"))["c"].hash, hashes[0]);
+
+        fs::remove_dir_all(&workdir).unwrap();
+    }
+
+    #[test]
+    fn last_commits_uncommitted() {
+        let (repo, _) = history("last-commits-uncommitted");
+        let workdir = repo.workdir().unwrap().to_path_buf();
+        fs::File::create(workdir.join("new")).unwrap();
+
+        let commits = Git::scan(&repo, None).unwrap().last_commits(&workdir);
+        assert!(!commits.contains_key("new"));
+        assert_eq!(commits.len(), 3);
+
+        fs::remove_dir_all(&workdir).unwrap();
+    }
+
+    /// Look up every file in one directory, as listing it would.
+    #[bench]
+    fn indexed_lookup(b: &mut Bencher) {
+        let git = git();
+        let paths: Vec<PathBuf> = (0 .. 1000).map(|f| PathBuf::from(format!("/repo/dir9/file{}", f))).collect();
+
+        b.iter(|| {
+            for path in paths.iter() {
+                git.status(path);
+            }
+            git.dir_status(Path::new("/repo/dir9"))
+        })
+    }
+
+    /// The same lookups, searching through the list of statuses each time,
+    /// which is how they used to be done.
+    #[bench]
+    fn linear_lookup(b: &mut Bencher) {
+        let list = statuses();
+        let paths: Vec<PathBuf> = (0 .. 1000).map(|f| PathBuf::from(format!("/repo/dir9/file{}", f))).collect();
+
+        b.iter(|| {
+            for path in paths.iter() {
+                list.iter().find(|p| &p.0 == path);
+            }
+            list.iter().filter(|p| p.0.starts_with("/repo/dir9")).fold(git2::Status::empty(), |a, b| a | b.1)
+        })
+    }
+}
@@ -0,0 +1,105 @@
+//! Inode flag support for Linux, the attributes set with `chattr`.
+extern crate libc;
+
+use std::fs;
+use std::os::unix::io::AsRawFd;
+use std::path::Path;
+use self::libc::{c_int, c_ulong};
+
+extern "C" {
+    fn ioctl(fd: c_int, request: c_ulong, ...) -> c_int;
+}
+
+// The command is defined as reading a long, so its number depends on the
+// size of one, but the kernel only ever writes an int.
+
+#[cfg(target_pointer_width = "64")]
+static FS_IOC_GETFLAGS: c_ulong = 0x80086601;
+
+#[cfg(target_pointer_width = "32")]
+static FS_IOC_GETFLAGS: c_ulong = 0x80046601;
+
+/// Each flag, along with the character `lsattr` uses for it, in the order
+/// `lsattr` prints them.
+static FLAGS: &'static [(u32, char)] = &[
+    (0x00000001, 's'),  // secure deletion
+    (0x00000002, 'u'),  // undeletable
+    (0x00000008, 'S'),  // synchronous updates
+    (0x00010000, 'D'),  // synchronous directory updates
+    (0x00000010, 'i'),  // immutable
+    (0x00000020, 'a'),  // append only
+    (0x00000040, 'd'),  // no dump
+    (0x00000080, 'A'),  // no atime updates
+    (0x00000004, 'c'),  // compressed
+    (0x00000800, 'E'),  // encrypted
+    (0x00004000, 'j'),  // data journalling
+    (0x00001000, 'I'),  // indexed directory
+    (0x00008000, 't'),  // no tail-merging
+    (0x00020000, 'T'),  // top of directory hierarchy
+    (0x00080000, 'e'),  // uses extents
+    (0x00800000, 'C'),  // no copy on write
+    (0x02000000, 'x'),  // direct access
+    (0x40000000, 'F'),  // case-insensitive directory
+    (0x10000000, 'N'),  // inline data
+    (0x20000000, 'P'),  // project hierarchy
+    (0x00100000, 'V'),  // verity protected
+    (0x00000400, 'm'),  // don't compress
+];
+
+/// The flags stored in a file's inode, such as whether it's immutable or
+/// append-only.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub struct InodeFlags {
+    bits: u32,
+}
+
+impl InodeFlags {
+
+    /// Read the flags of the file at the given path. Returns None if the
+    /// file can't be opened, or its filesystem doesn't support flags.
+    pub fn read(path: &Path) -> Option<InodeFlags> {
+        let file = match fs::File::open(path) {
+            Ok(f)  => f,
+            Err(_) => return None,
+        };
+
+        let mut bits: c_int = 0;
+        let result = unsafe { ioctl(file.as_raw_fd(), FS_IOC_GETFLAGS, &mut bits as *mut c_int) };
+
+        if result == 0 { Some(InodeFlags { bits: bits as u32 }) }
+                  else { None }
+    }
+
+    /// Each flag's `lsattr` character, or None in its place if it isn't set.
+    pub fn chars(&self) -> Vec<Option<char>> {
+        FLAGS.iter()
+             .map(|&(bit, c)| if self.bits & bit != 0 { Some(c) } else { None })
+             .collect()
+    }
+
+    /// The flags in the form `lsattr` prints them, such as
+    /// `----i---------e-------`.
+    pub fn to_lsattr_string(&self) -> String {
+        self.chars().iter().map(|c| c.unwrap_or('-')).collect()
+    }
+
+    /// Returns true if reading inode flags is implemented on this platform.
+    #[inline(always)]
+    pub fn feature_implemented() -> bool { true }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::InodeFlags;
+
+    #[test]
+    fn none() {
+        assert_eq!(InodeFlags { bits: 0 }.to_lsattr_string(), "----------------------")
+    }
+
+    #[test]
+    fn immutable_with_extents() {
+        assert_eq!(InodeFlags { bits: 0x80010 }.to_lsattr_string(), "----i---------e-------")
+    }
+}
@@ -1,5 +1,8 @@
 // Extended attribute support
 
+mod xattr_value;
+pub use self::xattr_value::{AclEntry, AclKind};
+
 #[cfg(target_os = "macos")] mod xattr_darwin;
 #[cfg(target_os = "macos")] pub use self::xattr_darwin::Attribute;
 
@@ -16,12 +19,32 @@ impl Attribute {
 
     /// Getter for name
     pub fn name(&self) -> &str {
-        unimplemented!()
+        ""
     }
 
     /// Getter for size
     pub fn size(&self) -> usize {
-        unimplemented!()
+        0
+    }
+
+    /// Getter for value
+    pub fn value(&self) -> Vec<u8> {
+        Vec::new()
+    }
+
+    /// The value in a readable form
+    pub fn describe_value(&self) -> String {
+        String::new()
+    }
+
+    /// Whether this attribute holds a POSIX ACL
+    pub fn acl_kind(&self) -> Option<AclKind> {
+        None
+    }
+
+    /// The entries of the POSIX ACL this attribute holds
+    pub fn acl_entries(&self) -> Option<Vec<AclEntry>> {
+        None
     }
 
     /// Lists the extended attributes. Follows symlinks like `stat`
@@ -39,24 +62,202 @@ impl Attribute {
 
 
 
+// Birth time support
+
+#[cfg(target_os = "linux")] mod birth_time_linux;
+#[cfg(target_os = "linux")] pub use self::birth_time_linux::birth_time;
+
+/// The time a file was created, which isn't known on this platform.
+#[cfg(not(target_os = "linux"))]
+pub fn birth_time(_: &::std::path::Path) -> Option<(i64, i64)> {
+    None
+}
+
+
+
+// Inode flag support
+
+#[cfg(target_os = "linux")] mod inode_flags_linux;
+#[cfg(target_os = "linux")] pub use self::inode_flags_linux::InodeFlags;
+
+#[cfg(not(target_os = "linux"))]
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub struct InodeFlags;
+
+#[cfg(not(target_os = "linux"))]
+impl InodeFlags {
+
+    /// Reads the flags of a file, which there aren't any of here.
+    pub fn read(_: &::std::path::Path) -> Option<InodeFlags> {
+        None
+    }
+
+    /// Each flag's `lsattr` character
+    pub fn chars(&self) -> Vec<Option<char>> {
+        Vec::new()
+    }
+
+    /// The flags in the form `lsattr` prints them
+    pub fn to_lsattr_string(&self) -> String {
+        String::new()
+    }
+
+    pub fn feature_implemented() -> bool { false }
+}
+
+
+
 // Git support
 
+/// The status of a file in one of Git's two areas: the index, which holds
+/// the staged changes, or the working tree, which holds the unstaged ones.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum GitStatus {
+    NotModified,
+    New,
+    Modified,
+    Deleted,
+    Renamed,
+    TypeChange,
+    Ignored,
+    Conflicted,
+}
+
+impl GitStatus {
+
+    /// The character used to represent this status, without any colour.
+    pub fn char(&self) -> char {
+        match *self {
+            GitStatus::NotModified => '-',
+            GitStatus::New         => 'A',
+            GitStatus::Modified    => 'M',
+            GitStatus::Deleted     => 'D',
+            GitStatus::Renamed     => 'R',
+            GitStatus::TypeChange  => 'T',
+            GitStatus::Ignored     => 'I',
+            GitStatus::Conflicted  => 'U',
+        }
+    }
+}
+
+/// A file's Git status in both the index and the working tree.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub struct GitStatuses {
+    pub staged:   GitStatus,
+    pub unstaged: GitStatus,
+}
+
+impl GitStatuses {
+
+    /// The two-character status string, such as `-M`, without any colour.
+    pub fn to_plain_string(&self) -> String {
+        format!("{}{}", self.staged.char(), self.unstaged.char())
+    }
+}
+
+/// A summary of where a repository's HEAD is, shown next to the names of
+/// directories inside it.
+#[derive(PartialEq, Debug, Clone)]
+pub struct GitHead {
+
+    /// The name of the checked-out branch, or the abbreviated hash of the
+    /// checked-out commit if HEAD is detached.
+    pub name: String,
+
+    /// Whether HEAD is detached, rather than pointing to a branch.
+    pub detached: bool,
+
+    /// How many commits the branch is ahead of and behind its upstream
+    /// branch, if it has one.
+    pub upstream: Option<(usize, usize)>,
+
+    /// Whether there are any changes in the index or the working tree.
+    pub dirty: bool,
+}
+
+/// The most recent commit to have changed a file.
+#[derive(PartialEq, Debug, Clone)]
+pub struct GitCommit {
+
+    /// The commit's hash, abbreviated to seven characters.
+    pub hash: String,
+
+    /// The name of the commit's author.
+    pub author: String,
+
+    /// When the commit was made, in seconds since the Unix epoch.
+    pub time: i64,
+}
+
+/// A repository whose working directory is being listed as a file, rather
+/// than one that a file is inside of.
+#[derive(PartialEq, Debug, Clone)]
+pub struct GitRepo {
+
+    /// Whether this is a submodule, which has a `.git` file pointing to its
+    /// repository in its superproject instead of a `.git` directory.
+    pub submodule: bool,
+
+    /// Where the repository's HEAD is, if it points to a commit.
+    pub head: Option<GitHead>,
+}
+
 #[cfg(feature="git")] mod git;
-#[cfg(feature="git")] pub use self::git::Git;
+#[cfg(feature="git")] pub use self::git::{Git, GitCache};
+
+#[cfg(not(feature="git"))]
+impl GitRepo {
+    pub fn at(_: &::std::path::Path) -> Option<GitRepo> {
+        None
+    }
+}
 
 #[cfg(not(feature="git"))] pub struct Git;
 #[cfg(not(feature="git"))] use std::old_path::posix::Path;
 #[cfg(not(feature="git"))]
 impl Git {
-    pub fn scan(_: &Path) -> Result<Git, ()> {
-        Err(())
-    }
-
-    pub fn status(&self, _: &Path) -> String {
+    pub fn status(&self, _: &Path) -> GitStatuses {
         panic!("Tried to access a Git repo without Git support!");
     }
 
-    pub fn dir_status(&self, path: &Path) -> String {
+    pub fn dir_status(&self, path: &Path) -> GitStatuses {
         self.status(path)
     }
+
+    pub fn is_ignored(&self, _: &Path) -> bool {
+        false
+    }
+
+    pub fn head(&self) -> Option<&GitHead> {
+        None
+    }
+
+    pub fn last_commits(&self, _: &Path) -> HashMap<String, GitCommit> {
+        HashMap::new()
+    }
+}
+
+#[cfg(not(feature="git"))] use std::sync::Arc;
+#[cfg(not(feature="git"))] use std::collections::HashMap;
+#[cfg(not(feature="git"))]
+#[derive(Clone)]
+pub struct GitCache;
+
+#[cfg(not(feature="git"))]
+impl GitCache {
+    pub fn new() -> GitCache {
+        GitCache
+    }
+
+    pub fn with_ref(_: Option<String>) -> GitCache {
+        GitCache
+    }
+
+    pub fn get(&self, _: &::std::path::Path) -> Option<Arc<Git>> {
+        None
+    }
+
+    pub fn has_rev(_: &::std::path::Path, _: &str) -> bool {
+        true
+    }
 }
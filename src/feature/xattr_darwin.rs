@@ -1,6 +1,7 @@
 //! Extended attribute support for darwin
 extern crate libc;
 
+use std::ffi::CString;
 use std::io;
 use std::path::Path;
 use std::ptr;
@@ -35,6 +36,12 @@ pub enum ListFlags {
 pub struct Attribute {
     name: String,
     size: usize,
+
+    // The value doesn't get read until it's asked for, as most listings
+    // only need the name and size, so enough is kept to read it later.
+    c_path: CString,
+    c_name: CString,
+    c_flags: c_int,
 }
 
 impl Attribute {
@@ -86,7 +93,10 @@ impl Attribute {
                                 // see man listxattr
                                 mem::transmute::<&[u8], &str>(&buf[start..end]).to_string()
                             },
-                            size: size as usize
+                            size: size as usize,
+                            c_path: c_path.clone(),
+                            c_name: CString::new(buf[start..end].to_vec()).unwrap(),
+                            c_flags: c_flags,
                         });
                     }
                     start = c_end;
@@ -110,6 +120,23 @@ impl Attribute {
         self.size
     }
 
+    /// The value of the attribute, which gets read from the file each time
+    /// it's asked for.
+    pub fn value(&self) -> Vec<u8> {
+        // The value could have changed size since it was listed, so only
+        // keep what was actually read.
+        let mut value = vec![0u8; self.size];
+        let read = unsafe {
+            getxattr(
+                self.c_path.as_ptr(),
+                self.c_name.as_ptr(),
+                value.as_mut_ptr() as *mut c_void, self.size as size_t, 0, self.c_flags
+            )
+        };
+        value.truncate(if read > 0 { read as usize } else { 0 });
+        value
+    }
+
     /// Lists the extended attributes.
     /// Follows symlinks like `stat`
     pub fn list(path: &Path) -> io::Result<Vec<Attribute>> {
@@ -19,7 +19,7 @@ extern "C" {
 }
 
 /// Attributes which can be passed to `Attribute::list_with_flags`
-#[derive(Copy, Clone)]
+#[derive(Debug, Copy, Clone)]
 pub enum FollowSymlinks {
     Yes,
     No
@@ -30,6 +30,12 @@ pub enum FollowSymlinks {
 pub struct Attribute {
     name: String,
     size: usize,
+
+    // The value doesn't get read until it's asked for, as most listings
+    // only need the name and size, so enough is kept to read it later.
+    c_path: CString,
+    c_name: CString,
+    follow: FollowSymlinks,
 }
 
 impl Attribute {
@@ -77,7 +83,10 @@ impl Attribute {
                     if size > 0 {
                         names.push(Attribute {
                             name: String::from_utf8_lossy(&buf[start..end]).into_owned(),
-                            size: size as usize
+                            size: size as usize,
+                            c_path: c_path.clone(),
+                            c_name: CString::new(buf[start..end].to_vec()).unwrap(),
+                            follow: do_follow,
                         });
                     }
                     start = c_end;
@@ -101,6 +110,28 @@ impl Attribute {
         self.size
     }
 
+    /// The value of the attribute, which gets read from the file each time
+    /// it's asked for.
+    pub fn value(&self) -> Vec<u8> {
+        let getxattr = match self.follow {
+            FollowSymlinks::Yes => getxattr,
+            FollowSymlinks::No => lgetxattr,
+        };
+
+        // The value could have changed size since it was listed, so only
+        // keep what was actually read.
+        let mut value = vec![0u8; self.size];
+        let read = unsafe {
+            getxattr(
+                self.c_path.as_ptr(),
+                self.c_name.as_ptr(),
+                value.as_mut_ptr() as *mut c_void, self.size as size_t
+            )
+        };
+        value.truncate(if read > 0 { read as usize } else { 0 });
+        value
+    }
+
     /// Lists the extended attributes.
     /// Follows symlinks like `stat`
     pub fn list(path: &Path) -> io::Result<Vec<Attribute>> {
@@ -0,0 +1,336 @@
+//! Turning the values of extended attributes into something readable.
+//!
+//! Most attributes hold either text or an opaque blob, but a few that the
+//! kernel uses have a binary format of their own, which gets decoded into
+//! the same text that `getcap` or `getfacl` would print.
+
+/// The longest a value gets before it's cut off, in characters for text, or
+/// in bytes for binary values shown as hex.
+const MAX_TEXT_LENGTH: usize = 64;
+const MAX_HEX_LENGTH: usize = 32;
+
+/// The names of the Linux capabilities, by their bit number.
+static CAPABILITIES: &'static [&'static str] = &[
+    "chown", "dac_override", "dac_read_search", "fowner", "fsetid", "kill",
+    "setgid", "setuid", "setpcap", "linux_immutable", "net_bind_service",
+    "net_broadcast", "net_admin", "net_raw", "ipc_lock", "ipc_owner",
+    "sys_module", "sys_rawio", "sys_chroot", "sys_ptrace", "sys_pacct",
+    "sys_admin", "sys_boot", "sys_nice", "sys_resource", "sys_time",
+    "sys_tty_config", "mknod", "lease", "audit_write", "audit_control",
+    "setfcap", "mac_override", "mac_admin", "syslog", "wake_alarm",
+    "block_suspend", "audit_read", "perfmon", "bpf", "checkpoint_restore",
+];
+
+/// The tags that say who a POSIX ACL entry applies to.
+const ACL_USER_OBJ:  u16 = 0x01;
+const ACL_USER:      u16 = 0x02;
+const ACL_GROUP_OBJ: u16 = 0x04;
+const ACL_GROUP:     u16 = 0x08;
+const ACL_MASK:      u16 = 0x10;
+const ACL_OTHER:     u16 = 0x20;
+
+/// Which of a file's two POSIX ACLs an attribute holds.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum AclKind {
+
+    /// The ACL that controls access to the file itself.
+    Access,
+
+    /// The ACL that new files created in a directory start off with.
+    Default,
+}
+
+/// One entry in a POSIX access control list, giving a set of permissions to
+/// a user, a group, or everyone else.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub struct AclEntry {
+
+    /// Who the entry applies to, as one of the `ACL_` tags.
+    pub tag: u16,
+
+    /// The read, write, and execute bits, in that order from high to low.
+    pub perm: u16,
+
+    /// The user or group ID, for entries about a specific user or group.
+    pub id: u32,
+}
+
+impl AclEntry {
+
+    /// Whether this entry is one of the three that every file has, which
+    /// mirror its owner, group, and other permission bits.
+    pub fn is_basic(&self) -> bool {
+        self.tag == ACL_USER_OBJ || self.tag == ACL_GROUP_OBJ || self.tag == ACL_OTHER
+    }
+
+    /// Whether this entry is for a specific user, whose ID it holds.
+    pub fn is_named_user(&self) -> bool {
+        self.tag == ACL_USER
+    }
+
+    /// Whether this entry is for a specific group, whose ID it holds.
+    pub fn is_named_group(&self) -> bool {
+        self.tag == ACL_GROUP
+    }
+
+    /// This entry in the form `getfacl` uses, such as `user:1000:r-x`.
+    pub fn to_string(&self) -> String {
+        self.to_string_with_name(None)
+    }
+
+    /// This entry in the form `getfacl` uses, with the given user or group
+    /// name in place of the ID, such as `user:alice:r-x`.
+    pub fn to_string_with_name(&self, name: Option<&str>) -> String {
+        let id = match name {
+            Some(n) => n.to_string(),
+            None    => self.id.to_string(),
+        };
+
+        let (kind, id) = match self.tag {
+            ACL_USER_OBJ  => ("user",  String::new()),
+            ACL_USER      => ("user",  id),
+            ACL_GROUP_OBJ => ("group", String::new()),
+            ACL_GROUP     => ("group", id),
+            ACL_MASK      => ("mask",  String::new()),
+            ACL_OTHER     => ("other", String::new()),
+            _             => ("unknown", id),
+        };
+
+        let bit = |b, c| if self.perm & b != 0 { c } else { '-' };
+        format!("{}:{}:{}{}{}", kind, id, bit(4, 'r'), bit(2, 'w'), bit(1, 'x'))
+    }
+}
+
+// These only depend on an attribute's name and value, so they're the same
+// whichever platform the attribute was read on.
+
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+impl super::Attribute {
+
+    /// The value in a readable form, decoding the attributes with a known
+    /// binary format, and shortening long ones.
+    pub fn describe_value(&self) -> String {
+        describe(self.name(), &self.value())
+    }
+
+    /// Whether this attribute holds a POSIX ACL, and if so, whether it's the
+    /// default ACL that new files in a directory get.
+    pub fn acl_kind(&self) -> Option<AclKind> {
+        match self.name() {
+            "system.posix_acl_access"  => Some(AclKind::Access),
+            "system.posix_acl_default" => Some(AclKind::Default),
+            _                          => None,
+        }
+    }
+
+    /// The entries of the POSIX ACL this attribute holds, if it holds one
+    /// and it can be decoded.
+    pub fn acl_entries(&self) -> Option<Vec<AclEntry>> {
+        self.acl_kind().and_then(|_| acl(&self.value()))
+    }
+}
+
+/// Describe the value of the extended attribute with the given name.
+pub fn describe(name: &str, value: &[u8]) -> String {
+    let decoded = match name {
+        "security.capability"      => capabilities(value),
+        "system.posix_acl_access"  => acl(value).map(|a| acl_string(&a)),
+        "system.posix_acl_default" => acl(value).map(|a| acl_string(&a)),
+        _                          => None,
+    };
+
+    match decoded {
+        Some(text) => text,
+        None       => raw(value),
+    }
+}
+
+/// Show a value as quoted text if it's printable, or as hex if it isn't,
+/// cutting it short if it's too long. A trailing NUL, which a lot of
+/// programs include when storing a string, doesn't count as binary.
+fn raw(value: &[u8]) -> String {
+    let text = match value.last() {
+        Some(&0) => &value[.. value.len() - 1],
+        _        => value,
+    };
+
+    if let Ok(s) = ::std::str::from_utf8(text) {
+        if !s.chars().any(|c| c.is_control()) {
+            let mut chars: String = s.chars().take(MAX_TEXT_LENGTH).collect();
+            if s.chars().count() > MAX_TEXT_LENGTH {
+                chars.push_str("...");
+            }
+
+            return format!("\"{}\"", chars);
+        }
+    }
+
+    let mut hex = "0x".to_string();
+    for byte in value.iter().take(MAX_HEX_LENGTH) {
+        hex.push_str(&format!("{:02x}", byte));
+    }
+
+    if value.len() > MAX_HEX_LENGTH {
+        hex.push_str("...");
+    }
+
+    hex
+}
+
+/// Read a little-endian u32 starting at the given offset.
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    (bytes[offset] as u32)
+        | (bytes[offset + 1] as u32) << 8
+        | (bytes[offset + 2] as u32) << 16
+        | (bytes[offset + 3] as u32) << 24
+}
+
+/// Read a little-endian u16 starting at the given offset.
+fn read_u16(bytes: &[u8], offset: usize) -> u16 {
+    (bytes[offset] as u16) | (bytes[offset + 1] as u16) << 8
+}
+
+/// Decode a `vfs_cap_data` structure into the same form as `getcap`, such
+/// as `cap_net_bind_service,cap_net_raw=ep`. Capabilities with the same set
+/// of flags get grouped together.
+///
+/// The structure starts with a word holding the revision number and the
+/// effective flag, followed by pairs of permitted and inheritable words:
+/// one pair in revision 1, and two pairs in revisions 2 and 3, the last of
+/// which has the ID of the namespace's root user after them.
+fn capabilities(value: &[u8]) -> Option<String> {
+    if value.len() < 4 {
+        return None;
+    }
+
+    let magic = read_u32(value, 0);
+    let words = match magic & 0xFF000000 {
+        0x01000000 => 1,
+        0x02000000 => 2,
+        0x03000000 => 2,
+        _          => return None,
+    };
+
+    if value.len() < 4 + words * 8 {
+        return None;
+    }
+
+    let effective = magic & 1 != 0;
+    let mut groups: Vec<(String, Vec<&str>)> = Vec::new();
+
+    for (bit, &name) in CAPABILITIES.iter().enumerate() {
+        let word = bit / 32;
+        if word >= words {
+            break;
+        }
+
+        let mask = 1 << (bit % 32);
+        let permitted   = read_u32(value, 4 + word * 8) & mask != 0;
+        let inheritable = read_u32(value, 8 + word * 8) & mask != 0;
+
+        let mut flags = String::new();
+        if effective && permitted { flags.push('e') }
+        if inheritable            { flags.push('i') }
+        if permitted              { flags.push('p') }
+
+        if flags.is_empty() {
+            continue;
+        }
+
+        match groups.iter().position(|&(ref f, _)| *f == flags) {
+            Some(i) => groups[i].1.push(name),
+            None    => groups.push((flags, vec![ name ])),
+        }
+    }
+
+    let groups: Vec<String> = groups.iter().map(|&(ref flags, ref names)| {
+        let names: Vec<String> = names.iter().map(|n| format!("cap_{}", n)).collect();
+        format!("{}={}", names.connect(","), flags)
+    }).collect();
+
+    Some(groups.connect(" "))
+}
+
+/// Decode a `posix_acl_xattr` structure: a version number of 2, followed by
+/// entries that are each a tag, a set of permissions, and an ID.
+pub fn acl(value: &[u8]) -> Option<Vec<AclEntry>> {
+    if value.len() < 4 || read_u32(value, 0) != 2 || (value.len() - 4) % 8 != 0 {
+        return None;
+    }
+
+    let entries = (0 .. (value.len() - 4) / 8).map(|i| {
+        let offset = 4 + i * 8;
+        AclEntry {
+            tag:  read_u16(value, offset),
+            perm: read_u16(value, offset + 2),
+            id:   read_u32(value, offset + 4),
+        }
+    }).collect();
+
+    Some(entries)
+}
+
+fn acl_string(entries: &[AclEntry]) -> String {
+    let strings: Vec<String> = entries.iter().map(|e| e.to_string()).collect();
+    strings.connect(",")
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::{describe, acl, AclEntry};
+
+    #[test]
+    fn text() {
+        assert_eq!(describe("user.note", b"hello\0"), "\"hello\"")
+    }
+
+    #[test]
+    fn long_text() {
+        let value: Vec<u8> = ::std::iter::repeat(b'a').take(100).collect();
+        let expected = format!("\"{}...\"", ::std::iter::repeat("a").take(64).collect::<String>());
+        assert_eq!(describe("user.note", &value), expected)
+    }
+
+    #[test]
+    fn binary() {
+        assert_eq!(describe("user.blob", &[ 0xde, 0xad, 0x00, 0xef ]), "0xdead00ef")
+    }
+
+    #[test]
+    fn capabilities() {
+        // Revision 2, effective, with cap_net_bind_service (bit 10) and
+        // cap_net_raw (bit 13) permitted.
+        let value = [ 0x01, 0, 0, 0x02,  0x00, 0x24, 0, 0,  0, 0, 0, 0,  0, 0, 0, 0,  0, 0, 0, 0 ];
+        assert_eq!(describe("security.capability", &value), "cap_net_bind_service,cap_net_raw=ep")
+    }
+
+    #[test]
+    fn access_acl() {
+        let value = [ 2, 0, 0, 0,
+                      0x01, 0, 6, 0,  0xff, 0xff, 0xff, 0xff,
+                      0x02, 0, 5, 0,  0xe8, 0x03, 0, 0,
+                      0x04, 0, 4, 0,  0xff, 0xff, 0xff, 0xff,
+                      0x10, 0, 5, 0,  0xff, 0xff, 0xff, 0xff,
+                      0x20, 0, 0, 0,  0xff, 0xff, 0xff, 0xff ];
+
+        assert_eq!(describe("system.posix_acl_access", &value),
+                   "user::rw-,user:1000:r-x,group::r--,mask::r-x,other::---")
+    }
+
+    #[test]
+    fn bad_acl() {
+        assert_eq!(acl(&[ 1, 0, 0, 0 ]), None)
+    }
+
+    #[test]
+    fn named_entry() {
+        let entry = AclEntry { tag: 0x02, perm: 7, id: 1000 };
+        assert_eq!(entry.to_string_with_name(Some("alice")), "user:alice:rwx")
+    }
+
+    #[test]
+    fn basic_entry() {
+        assert!(AclEntry { tag: 0x04, perm: 4, id: 0 }.is_basic())
+    }
+}
@@ -1,4 +1,6 @@
 use std::ascii::AsciiExt;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
 use std::env::current_dir;
 use std::fs;
 use std::io;
@@ -6,10 +8,9 @@ use std::os::unix;
 use std::os::unix::raw::mode_t;
 use std::os::unix::fs::{MetadataExt, PermissionsExt};
 use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
 
-use ansi_term::{ANSIString, ANSIStrings, Colour, Style};
-use ansi_term::Style::Plain;
-use ansi_term::Colour::{Red, Green, Yellow, Blue, Purple, Cyan, Fixed};
+use ansi_term::{ANSIString, ANSIStrings, Style};
 
 use users::Users;
 
@@ -26,13 +27,28 @@ use column::{Column, Cell};
 use column::Column::*;
 use dir::Dir;
 use filetype::HasType;
-use options::{SizeFormat, TimeType};
+use options::{SizeFormat, TimeStyle, TimeType};
+use output::{Colours, git_head_view};
 use output::details::UserLocale;
-use feature::Attribute;
-
-/// This grey value is directly in between white and black, so it's guaranteed
-/// to show up on either backgrounded terminal.
-pub static GREY: Colour = Fixed(244);
+use time::format_local;
+use feature;
+use feature::{Attribute, GitCache, GitCommit, GitStatuses};
+use feature::InodeFlags as Flags;
+use feature::GitRepo as Repo;
+
+/// The bits of a file's mode that hold its type, along with the values for
+/// the types that aren't directories, regular files, or symlinks. These are
+/// the same on every Unix.
+static S_IFMT:   mode_t = 0o170000;
+static S_IFIFO:  mode_t = 0o010000;
+static S_IFCHR:  mode_t = 0o020000;
+static S_IFBLK:  mode_t = 0o060000;
+static S_IFSOCK: mode_t = 0o140000;
+
+/// The bits of a file's mode for the setuid, setgid, and sticky flags.
+static S_ISUID: mode_t = 0o4000;
+static S_ISGID: mode_t = 0o2000;
+static S_ISVTX: mode_t = 0o1000;
 
 /// A **File** is a wrapper around one of Rust's Path objects, along with
 /// associated data about the file.
@@ -49,6 +65,103 @@ pub struct File<'a> {
     pub stat:  fs::Metadata,
     pub xattrs: Vec<Attribute>,
     pub this:  Option<Dir>,
+
+    /// The total size of everything under this directory, if it has been
+    /// calculated. This is only done when the user asks for it, as it means
+    /// reading the entire subtree.
+    pub total_size: Option<TotalSize>,
+
+    /// The time this file was created, as seconds and nanoseconds, if it's
+    /// known. This needs a system call of its own, so it only gets read the
+    /// first time it's asked for, and is then kept for sorting and display.
+    created: RefCell<Option<Option<(i64, i64)>>>,
+}
+
+/// The **TotalSize** of a directory is the sum of the sizes of every file
+/// beneath it, including the directory itself, in the same way as `du`.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub struct TotalSize {
+
+    /// The apparent size, in bytes.
+    pub bytes: u64,
+
+    /// The number of 512-byte blocks actually taken up on disk.
+    pub blocks: u64,
+}
+
+/// A **SizeCache** holds the total size of every directory that has been
+/// added up so far, and gets shared by the whole listing. Working out the
+/// total of a directory also works out the totals of every directory
+/// beneath it, so a recursive listing only reads each subtree once.
+///
+/// It also remembers every hard-linked file that has been counted, so a
+/// file with several links is only counted once across the entire listing,
+/// the same as `du` given several paths. This means that a file linked from
+/// two sibling directories only counts towards the first one to be read.
+///
+/// Cloning a cache gives another handle to the same totals.
+#[derive(Clone)]
+pub struct SizeCache {
+    sizes: Arc<Mutex<Sizes>>,
+}
+
+struct Sizes {
+    totals: HashMap<PathBuf, TotalSize>,
+    seen: HashSet<(u64, u64)>,
+}
+
+impl SizeCache {
+
+    /// Create a new cache, with nothing counted yet.
+    pub fn new() -> SizeCache {
+        let sizes = Sizes { totals: HashMap::new(), seen: HashSet::new() };
+        SizeCache { sizes: Arc::new(Mutex::new(sizes)) }
+    }
+
+    /// Add up the sizes of everything under the given path, unless it has
+    /// been added up already. Symlinks aren't followed, files with several
+    /// hard links are only counted once, and anything that can't be read is
+    /// skipped.
+    pub fn total_size(&self, path: &Path, stat: &fs::Metadata) -> TotalSize {
+        self.sizes.lock().unwrap().total(path, stat)
+    }
+}
+
+impl Sizes {
+    fn total(&mut self, path: &Path, stat: &fs::Metadata) -> TotalSize {
+        if let Some(&total) = self.totals.get(path) {
+            return total;
+        }
+
+        let raw = stat.as_raw();
+
+        // Every link to a hard-linked file has the same device and inode
+        // numbers, so only the first one found gets counted.
+        if !stat.is_dir() && raw.nlink() > 1 && !self.seen.insert((raw.dev() as u64, raw.ino() as u64)) {
+            return TotalSize { bytes: 0, blocks: 0 };
+        }
+
+        let mut total = TotalSize { bytes: stat.len(), blocks: raw.blocks() as u64 };
+
+        if stat.is_dir() {
+            if let Ok(entries) = fs::read_dir(path) {
+                for entry in entries {
+                    if let Ok(entry) = entry {
+                        let child = entry.path();
+                        if let Ok(child_stat) = fs::symlink_metadata(&child) {
+                            let child_total = self.total(&child, &child_stat);
+                            total.bytes  += child_total.bytes;
+                            total.blocks += child_total.blocks;
+                        }
+                    }
+                }
+            }
+
+            self.totals.insert(path.to_path_buf(), total);
+        }
+
+        total
+    }
 }
 
 impl<'a> File<'a> {
@@ -57,19 +170,19 @@ impl<'a> File<'a> {
     ///
     /// This uses `symlink_metadata` instead of `metadata`, which doesn't
     /// follow symbolic links.
-    pub fn from_path(path: &Path, parent: Option<&'a Dir>, recurse: bool) -> io::Result<File<'a>> {
-        fs::symlink_metadata(path).map(|stat| File::with_stat(stat, path, parent, recurse))
+    pub fn from_path(path: &Path, parent: Option<&'a Dir>, recurse: bool, git_cache: &GitCache) -> io::Result<File<'a>> {
+        fs::symlink_metadata(path).map(|stat| File::with_stat(stat, path, parent, recurse, git_cache))
     }
 
     /// Create a new File object from the given Stat result, and other data.
-    pub fn with_stat(stat: fs::Metadata, path: &Path, parent: Option<&'a Dir>, recurse: bool) -> File<'a> {
+    pub fn with_stat(stat: fs::Metadata, path: &Path, parent: Option<&'a Dir>, recurse: bool, git_cache: &GitCache) -> File<'a> {
         let filename = path_filename(path);
 
         // If we are recursing, then the `this` field contains a Dir object
         // that represents the current File as a directory, if it is a
         // directory. This is used for the --tree option.
         let this = if recurse && stat.is_dir() {
-            Dir::readdir(path).ok()
+            Dir::readdir(path, git_cache).ok()
         }
         else {
             None
@@ -83,6 +196,8 @@ impl<'a> File<'a> {
             xattrs: Attribute::llist(path).unwrap_or(Vec::new()),
             name:   filename.to_string(),
             this:   this,
+            total_size: None,
+            created: RefCell::new(None),
         }
     }
 
@@ -104,7 +219,50 @@ impl<'a> File<'a> {
     }
 
     pub fn is_pipe(&self) -> bool {
-        false  // TODO: Still waiting on this one...
+        self.type_bits() == S_IFIFO
+    }
+
+    pub fn is_socket(&self) -> bool {
+        self.type_bits() == S_IFSOCK
+    }
+
+    pub fn is_block_device(&self) -> bool {
+        self.type_bits() == S_IFBLK
+    }
+
+    pub fn is_char_device(&self) -> bool {
+        self.type_bits() == S_IFCHR
+    }
+
+    /// The file type part of this file's mode.
+    fn type_bits(&self) -> mode_t {
+        self.stat.as_raw().mode() & S_IFMT
+    }
+
+    /// Work out the total size of this directory's contents, if it is one,
+    /// using the given cache to avoid reading the same subtree twice.
+    pub fn calculate_total_size(&mut self, cache: &SizeCache) {
+        if self.is_directory() {
+            self.total_size = Some(cache.total_size(&self.path, &self.stat));
+        }
+    }
+
+    /// This file's size in bytes, which is the total size of its contents
+    /// for directories that have had it calculated.
+    pub fn size(&self) -> u64 {
+        match self.total_size {
+            Some(total) => total.bytes,
+            None        => self.stat.len(),
+        }
+    }
+
+    /// The number of blocks this file takes up on disk, including its
+    /// contents for directories that have had their total calculated.
+    pub fn block_count(&self) -> u64 {
+        match self.total_size {
+            Some(total) => total.blocks,
+            None        => self.stat.as_raw().blocks() as u64,
+        }
     }
 
     /// Whether this file is a dotfile or not.
@@ -119,17 +277,24 @@ impl<'a> File<'a> {
     }
 
     /// Get the data for a column, formatted as a coloured string.
-    pub fn display<U: Users>(&self, column: &Column, users_cache: &mut U, locale: &UserLocale) -> Cell {
+    pub fn display<U: Users>(&self, column: &Column, colours: &Colours, users_cache: &mut U, locale: &UserLocale) -> Cell {
         match *column {
-            Permissions     => self.permissions_string(),
-            FileSize(f)     => self.file_size(f, &locale.numeric),
-            Timestamp(t, y) => self.timestamp(t, y, &locale.time),
-            HardLinks       => self.hard_links(&locale.numeric),
-            Inode           => self.inode(),
-            Blocks          => self.blocks(&locale.numeric),
-            User            => self.user(users_cache),
-            Group           => self.group(users_cache),
-            GitStatus       => self.git_status(),
+            Permissions     => self.permissions_string(colours),
+            Octal           => self.octal_permissions(colours),
+            FileSize(f)     => self.file_size(f, colours, &locale.numeric),
+            Timestamp(t, ref s, now) => self.timestamp(t, s, now, colours, &locale.time),
+            HardLinks       => self.hard_links(colours, &locale.numeric),
+            Inode           => self.inode(colours),
+            Blocks          => self.blocks(colours, &locale.numeric),
+            User            => self.user(colours, users_cache),
+            Group           => self.group(colours, users_cache),
+            SecurityContext => self.security_context_cell(colours),
+            Column::InodeFlags => self.inode_flags_cell(colours),
+            GitStatus       => self.git_status(colours),
+            GitCommitHash   => self.git_commit_hash(colours),
+            GitCommitAuthor => self.git_commit_author(colours),
+            GitCommitDate(now) => self.git_commit_date(now, colours),
+            Column::GitRepo => self.git_repo_cell(colours),
         }
     }
 
@@ -138,12 +303,12 @@ impl<'a> File<'a> {
     ///
     /// It consists of the file name coloured in the appropriate style,
     /// with special formatting for a symlink.
-    pub fn file_name_view(&self) -> String {
+    pub fn file_name_view(&self, colours: &Colours) -> String {
         if self.is_link() {
-            self.symlink_file_name_view()
+            self.symlink_file_name_view(colours)
         }
         else {
-            self.file_colour().paint(&*self.name).to_string()
+            self.file_colour(colours).paint(&*self.name).to_string()
         }
     }
 
@@ -155,9 +320,9 @@ impl<'a> File<'a> {
     /// an error, highlight the target and arrow in red. The error would
     /// be shown out of context, and it's almost always because the
     /// target doesn't exist.
-    fn symlink_file_name_view(&self) -> String {
+    fn symlink_file_name_view(&self, colours: &Colours) -> String {
         let name = &*self.name;
-        let style = self.file_colour();
+        let style = self.file_colour(colours);
 
         if let Ok(path) = fs::read_link(&self.path) {
             let target_path = match self.dir {
@@ -190,14 +355,14 @@ impl<'a> File<'a> {
 
                     format!("{} {} {}",
                             style.paint(name),
-                            GREY.paint("=>"),
-                            ANSIStrings(&[ Cyan.paint(&path_prefix),
-                                           file.file_colour().paint(&file.name) ]))
+                            colours.punctuation.paint("=>"),
+                            ANSIStrings(&[ colours.symlink_path.paint(&path_prefix),
+                                           file.file_colour(colours).paint(&file.name) ]))
                 },
                 Err(filename) => format!("{} {} {}",
                                          style.paint(name),
-                                         Red.paint("=>"),
-                                         Red.underline().paint(&filename)),
+                                         colours.broken_arrow.paint("=>"),
+                                         colours.broken_filename.paint(&filename)),
             }
         }
         else {
@@ -206,8 +371,18 @@ impl<'a> File<'a> {
     }
 
     /// The `ansi_term::Style` that this file's name should be painted.
-    pub fn file_colour(&self) -> Style {
-        self.get_type().style()
+    ///
+    /// Regular files can have their style picked by the end of their name,
+    /// if the user has configured any suffixes; otherwise, and for every
+    /// other kind of file, the style depends on the file's type.
+    pub fn file_colour(&self, colours: &Colours) -> Style {
+        if self.is_file() && !self.is_executable_file() {
+            if let Some(style) = colours.suffix_style(&self.name) {
+                return style;
+            }
+        }
+
+        self.get_type().style(&colours.filetypes)
     }
 
     /// The Unicode 'display width' of the filename.
@@ -238,6 +413,8 @@ impl<'a> File<'a> {
                 xattrs: Attribute::list(target_path).unwrap_or(Vec::new()),
                 name:   filename.to_string(),
                 this:   None,
+                total_size: None,
+                created: RefCell::new(None),
             })
         }
         else {
@@ -246,8 +423,8 @@ impl<'a> File<'a> {
     }
 
     /// This file's number of hard links as a coloured string.
-    fn hard_links(&self, locale: &locale::Numeric) -> Cell {
-        let style = if self.has_multiple_links() { Red.on(Yellow) } else { Red.normal() };
+    fn hard_links(&self, colours: &Colours, locale: &locale::Numeric) -> Cell {
+        let style = if self.has_multiple_links() { colours.links.multi_link_file } else { colours.links.normal };
         Cell::paint(style, &locale.format_int(self.stat.as_raw().nlink())[..])
     }
 
@@ -261,18 +438,18 @@ impl<'a> File<'a> {
     }
 
     /// This file's inode as a coloured string.
-    fn inode(&self) -> Cell {
+    fn inode(&self, colours: &Colours) -> Cell {
         let inode = self.stat.as_raw().ino();
-        Cell::paint(Purple.normal(), &inode.to_string()[..])
+        Cell::paint(colours.inode, &inode.to_string()[..])
     }
 
     /// This file's number of filesystem blocks (if available) as a coloured string.
-    fn blocks(&self, locale: &locale::Numeric) -> Cell {
-        if self.is_file() || self.is_link() {
-            Cell::paint(Cyan.normal(), &locale.format_int(self.stat.as_raw().blocks())[..])
+    fn blocks(&self, colours: &Colours, locale: &locale::Numeric) -> Cell {
+        if self.is_file() || self.is_link() || self.total_size.is_some() {
+            Cell::paint(colours.blocks, &locale.format_int(self.block_count())[..])
         }
         else {
-            Cell { text: GREY.paint("-").to_string(), length: 1 }
+            Cell { text: colours.punctuation.paint("-").to_string(), length: 1 }
         }
     }
 
@@ -281,7 +458,7 @@ impl<'a> File<'a> {
     /// If the user is not present, then it formats the uid as a number
     /// instead. This usually happens when a user is deleted, but still owns
     /// files.
-    fn user<U: Users>(&self, users_cache: &mut U) -> Cell {
+    fn user<U: Users>(&self, colours: &Colours, users_cache: &mut U) -> Cell {
         let uid = self.stat.as_raw().uid();
 
         let user_name = match users_cache.get_user_by_uid(uid) {
@@ -289,23 +466,23 @@ impl<'a> File<'a> {
             None => uid.to_string(),
         };
 
-        let style = if users_cache.get_current_uid() == uid { Yellow.bold() } else { Plain };
+        let style = if users_cache.get_current_uid() == uid { colours.users.user_you } else { colours.users.user_someone_else };
         Cell::paint(style, &*user_name)
     }
 
     /// This file's group name as a coloured string.
     ///
     /// As above, if not present, it formats the gid as a number instead.
-    fn group<U: Users>(&self, users_cache: &mut U) -> Cell {
+    fn group<U: Users>(&self, colours: &Colours, users_cache: &mut U) -> Cell {
         let gid = self.stat.as_raw().gid();
-        let mut style = Plain;
+        let mut style = colours.users.group_not_yours;
 
         let group_name = match users_cache.get_group_by_gid(gid as u32) {
             Some(group) => {
                 let current_uid = users_cache.get_current_uid();
                 if let Some(current_user) = users_cache.get_user_by_uid(current_uid) {
                     if current_user.primary_group == group.gid || group.members.contains(&current_user.name) {
-                        style = Yellow.bold();
+                        style = colours.users.group_yours;
                     }
                 }
                 group.name
@@ -316,31 +493,92 @@ impl<'a> File<'a> {
         Cell::paint(style, &*group_name)
     }
 
+    /// The flags in this file's inode, as set by `chattr`. Only regular
+    /// files and directories are looked at, as opening anything else to
+    /// read its flags could have side effects. Returns None for other
+    /// files, and for filesystems that don't support flags.
+    pub fn inode_flags(&self) -> Option<Flags> {
+        if self.is_file() || self.is_directory() { Flags::read(&self.path) }
+                                            else { None }
+    }
+
+    /// The inode flags in the form `lsattr` prints them, with the flags that
+    /// aren't set as dashes, or a single dash if there aren't any flags.
+    fn inode_flags_cell(&self, colours: &Colours) -> Cell {
+        let flags = match self.inode_flags() {
+            Some(f) => f,
+            None    => return Cell::paint(colours.punctuation, "-"),
+        };
+
+        let chars = flags.chars();
+        let mut text = String::new();
+        for c in chars.iter() {
+            match *c {
+                Some(c) => text.push_str(&colours.perms.attribute.paint(&c.to_string()).to_string()),
+                None    => text.push_str(&colours.punctuation.paint("-").to_string()),
+            }
+        }
+
+        Cell { text: text, length: chars.len() }
+    }
+
+    /// This file's SELinux security context, such as
+    /// `system_u:object_r:etc_t:s0`, read from its `security.selinux`
+    /// extended attribute. Returns None on systems without SELinux, or
+    /// without extended attributes at all.
+    pub fn security_context(&self) -> Option<String> {
+        self.xattrs.iter()
+                   .find(|a| a.name() == "security.selinux")
+                   .map(|a| String::from_utf8_lossy(&a.value()).trim_right_matches('\0').to_string())
+    }
+
+    /// The security context as a cell, or a `?` if there isn't one, like
+    /// `ls -Z` does.
+    fn security_context_cell(&self, colours: &Colours) -> Cell {
+        match self.security_context() {
+            Some(context) => Cell::paint(colours.security_context, &context),
+            None          => Cell::paint(colours.punctuation, "?"),
+        }
+    }
+
     /// This file's size, formatted using the given way, as a coloured string.
     ///
     /// For directories, no size is given. Although they do have a size on
     /// some filesystems, I've never looked at one of those numbers and gained
     /// any information from it, so by emitting "-" instead, the table is less
     /// cluttered with numbers.
-    fn file_size(&self, size_format: SizeFormat, locale: &locale::Numeric) -> Cell {
-        if self.is_directory() {
-            Cell { text: GREY.paint("-").to_string(), length: 1 }
+    ///
+    /// Device files don't have a size, so their major and minor device
+    /// numbers are shown instead, like `ls` does.
+    fn file_size(&self, size_format: SizeFormat, colours: &Colours, locale: &locale::Numeric) -> Cell {
+        if self.is_directory() && self.total_size.is_none() {
+            Cell { text: colours.punctuation.paint("-").to_string(), length: 1 }
+        }
+        else if self.is_block_device() || self.is_char_device() {
+            let (major, minor) = device_numbers(self.stat.as_raw().rdev() as u64);
+            let major = major.to_string();
+            let minor = minor.to_string();
+
+            Cell {
+                text: ANSIStrings( &[ colours.size.major.paint(&major[..]), colours.punctuation.paint(","), colours.size.minor.paint(&minor[..]) ]).to_string(),
+                length: major.len() + 1 + minor.len(),
+            }
         }
         else {
             let result = match size_format {
-                SizeFormat::DecimalBytes => decimal_prefix(self.stat.len() as f64),
-                SizeFormat::BinaryBytes  => binary_prefix(self.stat.len() as f64),
-                SizeFormat::JustBytes    => return Cell::paint(Green.bold(), &locale.format_int(self.stat.len())[..]),
+                SizeFormat::DecimalBytes => decimal_prefix(self.size() as f64),
+                SizeFormat::BinaryBytes  => binary_prefix(self.size() as f64),
+                SizeFormat::JustBytes    => return Cell::paint(colours.size.numbers, &locale.format_int(self.size())[..]),
             };
 
             match result {
-                Standalone(bytes) => Cell::paint(Green.bold(), &*bytes.to_string()),
+                Standalone(bytes) => Cell::paint(colours.size.numbers, &*bytes.to_string()),
                 Prefixed(prefix, n) => {
                     let number = if n < 10f64 { locale.format_float(n, 1) } else { locale.format_int(n as isize) };
                     let symbol = prefix.symbol();
 
                     Cell {
-                        text: ANSIStrings( &[ Green.bold().paint(&number[..]), Green.paint(symbol) ]).to_string(),
+                        text: ANSIStrings( &[ colours.size.numbers.paint(&number[..]), colours.size.unit.paint(symbol) ]).to_string(),
                         length: number.len() + symbol.len(),
                     }
                 }
@@ -348,55 +586,122 @@ impl<'a> File<'a> {
         }
     }
 
-    fn timestamp(&self, time_type: TimeType, current_year: i64, locale: &locale::Time) -> Cell {
+    /// One of this file's timestamps, as the number of seconds since the
+    /// Unix epoch. Only the creation time can be missing, as not every
+    /// system or filesystem records it.
+    pub fn timestamp_seconds(&self, time_type: TimeType) -> Option<i64> {
+        match time_type {
+            TimeType::FileAccessed => Some(self.stat.as_raw().atime() as i64),
+            TimeType::FileModified => Some(self.stat.as_raw().mtime() as i64),
+            TimeType::FileChanged  => Some(self.stat.as_raw().ctime() as i64),
+            TimeType::FileCreated  => self.birth_time(),
+        }
+    }
 
-        let time_in_seconds = match time_type {
-            TimeType::FileAccessed => self.stat.as_raw().atime(),
-            TimeType::FileModified => self.stat.as_raw().mtime(),
-            TimeType::FileCreated  => self.stat.as_raw().ctime(),
-        } as i64;
+    /// The nanoseconds past the second of one of this file's timestamps,
+    /// or zero if the timestamp isn't known.
+    pub fn timestamp_nanoseconds(&self, time_type: TimeType) -> i64 {
+        match time_type {
+            TimeType::FileAccessed => self.stat.as_raw().atime_nsec() as i64,
+            TimeType::FileModified => self.stat.as_raw().mtime_nsec() as i64,
+            TimeType::FileChanged  => self.stat.as_raw().ctime_nsec() as i64,
+            TimeType::FileCreated  => self.created().map(|t| t.1).unwrap_or(0),
+        }
+    }
 
-        let date = LocalDateTime::at(time_in_seconds);
+    /// The time this file was created, if it's known. This isn't the same
+    /// as the time its inode last changed, which is what `ctime` holds, so
+    /// it has to be read separately.
+    pub fn birth_time(&self) -> Option<i64> {
+        self.created().map(|t| t.0)
+    }
 
-        let format = if date.year() == current_year {
-                DateFormat::parse("{2>:D} {:M} {2>:h}:{02>:m}").unwrap()
-            }
-            else {
-                DateFormat::parse("{2>:D} {:M} {5>:Y}").unwrap()
-            };
+    /// The seconds and nanoseconds of the time this file was created,
+    /// reading them the first time they're needed.
+    fn created(&self) -> Option<(i64, i64)> {
+        let mut created = self.created.borrow_mut();
+        if created.is_none() {
+            *created = Some(feature::birth_time(&self.path));
+        }
 
-        Cell::paint(Blue.normal(), &format.format(date, locale))
+        created.unwrap()
+    }
+
+    /// One of this file's timestamps, formatted in the given style. The
+    /// current time is used to decide whether to show the year, and to say
+    /// how long ago it was for the relative style.
+    fn timestamp(&self, time_type: TimeType, style: &TimeStyle, now: i64, colours: &Colours, locale: &locale::Time) -> Cell {
+        let seconds = match self.timestamp_seconds(time_type) {
+            Some(s) => s,
+            None    => return Cell::paint(colours.punctuation, "-"),
+        };
+
+        let date = LocalDateTime::at(seconds);
+        let this_year = date.year() == LocalDateTime::at(now).year();
+        let nanoseconds = self.timestamp_nanoseconds(time_type);
+
+        let text = match *style {
+            TimeStyle::Default => {
+                let format = if this_year { DateFormat::parse("{2>:D} {:M} {2>:h}:{02>:m}").unwrap() }
+                                     else { DateFormat::parse("{2>:D} {:M} {5>:Y}").unwrap() };
+                format.format(date, locale)
+            },
+            TimeStyle::ISO => {
+                if this_year { format_local("%m-%d %H:%M", seconds, nanoseconds) }
+                        else { format_local("%Y-%m-%d", seconds, nanoseconds) }
+            },
+            TimeStyle::LongISO       => format_local("%Y-%m-%d %H:%M", seconds, nanoseconds),
+            TimeStyle::FullISO       => format_local("%Y-%m-%d %H:%M:%S.%N %z", seconds, nanoseconds),
+            TimeStyle::Relative      => relative_time(now - seconds),
+            TimeStyle::Custom(ref f) => format_local(f, seconds, nanoseconds),
+        };
+
+        Cell::paint(colours.date, &text)
     }
 
     /// This file's type, represented by a coloured character.
     ///
     /// Although the file type can usually be guessed from the colour of the
     /// file, `ls` puts this character there, so people will expect it.
-    fn type_char(&self) -> ANSIString {
+    fn type_char(&self, colours: &Colours) -> ANSIString {
         if self.is_file() {
-            Plain.paint(".")
+            colours.filetypes.normal.paint(".")
         }
         else if self.is_directory() {
-            Blue.paint("d")
+            colours.filetypes.directory.paint("d")
         }
         else if self.is_pipe() {
-            Yellow.paint("|")
+            colours.filetypes.pipe.paint("p")
         }
         else if self.is_link() {
-            Cyan.paint("l")
+            colours.filetypes.symlink.paint("l")
+        }
+        else if self.is_block_device() {
+            colours.filetypes.block_device.paint("b")
+        }
+        else if self.is_char_device() {
+            colours.filetypes.char_device.paint("c")
+        }
+        else if self.is_socket() {
+            colours.filetypes.socket.paint("s")
         }
         else {
-            Purple.paint("?")
+            colours.filetypes.special.paint("?")
         }
     }
 
-    /// Marker indicating that the file contains extended attributes
-    ///
-    /// Returns "@" or  " ” depending on wheter the file contains an extented
-    /// attribute or not. Also returns “ ” in case the attributes cannot be read
-    /// for some reason.
-    fn attribute_marker(&self) -> ANSIString {
-        if self.xattrs.len() > 0 { Plain.paint("@") } else { Plain.paint(" ") }
+    /// Whether this file has an access or default POSIX ACL.
+    pub fn has_acl(&self) -> bool {
+        self.xattrs.iter().any(|a| a.acl_kind().is_some())
+    }
+
+    /// The character after the permission bits: `+` if the file has an ACL,
+    /// which takes priority, as ACLs are stored as extended attributes, or
+    /// `@` if it has any other extended attributes.
+    fn attribute_marker(&self, colours: &Colours) -> ANSIString {
+        if self.has_acl()             { colours.perms.attribute.paint("+") }
+        else if self.xattrs.len() > 0 { colours.perms.attribute.paint("@") }
+        else                          { colours.perms.attribute.paint(" ") }
     }
 
     /// Generate the "rwxrwxrwx" permissions string, like how ls does it.
@@ -404,39 +709,64 @@ impl<'a> File<'a> {
     /// Each character is given its own colour. The first three permission
     /// bits are bold because they're the ones used most often, and executable
     /// files are underlined to make them stand out more.
-    fn permissions_string(&self) -> Cell {
+    pub fn permissions_string(&self, colours: &Colours) -> Cell {
 
-        let bits = self.stat.permissions().mode();
-        let executable_colour = if self.is_file() { Green.bold().underline() }
-                                                         else { Green.bold() };
+        let bits = self.stat.as_raw().mode();
+        let p = &colours.perms;
+        let executable_colour = if self.is_file() { p.user_execute_file }
+                                                         else { p.user_execute_other };
 
         let string = ANSIStrings(&[
-            self.type_char(),
-            File::permission_bit(bits, unix::fs::USER_READ,     "r", Yellow.bold()),
-            File::permission_bit(bits, unix::fs::USER_WRITE,    "w", Red.bold()),
-            File::permission_bit(bits, unix::fs::USER_EXECUTE,  "x", executable_colour),
-            File::permission_bit(bits, unix::fs::GROUP_READ,    "r", Yellow.normal()),
-            File::permission_bit(bits, unix::fs::GROUP_WRITE,   "w", Red.normal()),
-            File::permission_bit(bits, unix::fs::GROUP_EXECUTE, "x", Green.normal()),
-            File::permission_bit(bits, unix::fs::OTHER_READ,    "r", Yellow.normal()),
-            File::permission_bit(bits, unix::fs::OTHER_WRITE,   "w", Red.normal()),
-            File::permission_bit(bits, unix::fs::OTHER_EXECUTE, "x", Green.normal()),
-            self.attribute_marker()
+            self.type_char(colours),
+            File::permission_bit(bits, unix::fs::USER_READ,     "r", p.user_read,     colours),
+            File::permission_bit(bits, unix::fs::USER_WRITE,    "w", p.user_write,    colours),
+            File::execute_bit(bits, unix::fs::USER_EXECUTE,  executable_colour, S_ISUID, ("s", "S"), p.set_id, colours),
+            File::permission_bit(bits, unix::fs::GROUP_READ,    "r", p.group_read,    colours),
+            File::permission_bit(bits, unix::fs::GROUP_WRITE,   "w", p.group_write,   colours),
+            File::execute_bit(bits, unix::fs::GROUP_EXECUTE, p.group_execute, S_ISGID, ("s", "S"), p.set_id, colours),
+            File::permission_bit(bits, unix::fs::OTHER_READ,    "r", p.other_read,    colours),
+            File::permission_bit(bits, unix::fs::OTHER_WRITE,   "w", p.other_write,   colours),
+            File::execute_bit(bits, unix::fs::OTHER_EXECUTE, p.other_execute, S_ISVTX, ("t", "T"), p.sticky, colours),
+            self.attribute_marker(colours)
         ]).to_string();
 
         Cell { text: string, length: 11 }
     }
 
     /// Helper method for the permissions string.
-    fn permission_bit(bits: mode_t, bit: mode_t, character: &'static str, style: Style) -> ANSIString<'static> {
+    fn permission_bit(bits: mode_t, bit: mode_t, character: &'static str, style: Style, colours: &Colours) -> ANSIString<'static> {
         if bits & bit == bit {
             style.paint(character)
         }
         else {
-            GREY.paint("-")
+            colours.punctuation.paint("-")
         }
     }
 
+    /// Helper method for the execute bits of the permissions string, which
+    /// double up as the setuid, setgid, and sticky bits. When the special
+    /// bit is set, it's shown in lowercase if the file is also executable,
+    /// and in uppercase if it isn't, like how ls does it.
+    fn execute_bit(bits: mode_t, bit: mode_t, style: Style, special_bit: mode_t, special_chars: (&'static str, &'static str), special_style: Style, colours: &Colours) -> ANSIString<'static> {
+        if bits & special_bit == special_bit {
+            if bits & bit == bit { special_style.paint(special_chars.0) }
+                            else { special_style.paint(special_chars.1) }
+        }
+        else {
+            File::permission_bit(bits, bit, "x", style, colours)
+        }
+    }
+
+    /// This file's permission bits as an octal number, such as `0755`,
+    /// including the setuid, setgid, and sticky bits.
+    pub fn octal_permissions_string(&self) -> String {
+        format!("{:04o}", self.stat.as_raw().mode() & 0o7777)
+    }
+
+    fn octal_permissions(&self, colours: &Colours) -> Cell {
+        Cell::paint(colours.perms.octal, &self.octal_permissions_string())
+    }
+
     /// For this file, return a vector of alternate file paths that, if any of
     /// them exist, mean that *this* file should be coloured as `Compiled`.
     ///
@@ -472,9 +802,10 @@ impl<'a> File<'a> {
         }
     }
 
-    fn git_status(&self) -> Cell {
-        let status = match self.dir {
-            None    => GREY.paint("--").to_string(),
+    /// This file's Git status, if it's in a directory inside a repository.
+    pub fn git_statuses(&self) -> Option<GitStatuses> {
+        match self.dir {
+            None    => None,
             Some(d) => {
                 let cwd = match current_dir() {
                     Err(_)  => Path::new(".").join(&self.path),
@@ -483,12 +814,133 @@ impl<'a> File<'a> {
 
                 d.git_status(&cwd, self.is_directory())
             },
+        }
+    }
+
+    fn git_status(&self, colours: &Colours) -> Cell {
+        match self.git_statuses() {
+            None    => Cell { text: colours.punctuation.paint("--").to_string(), length: 2 },
+            Some(s) => git_statuses_cell(s, colours),
+        }
+    }
+
+    /// The two-character Git status to show before this file's name in the
+    /// grid and lines views, if it's wanted and the file is in a repository.
+    pub fn git_status_marker(&self, git: bool, colours: &Colours) -> Option<Cell> {
+        if git { self.git_statuses().map(|s| git_statuses_cell(s, colours)) }
+          else { None }
+    }
+
+    /// The most recent commit to have changed this file, if it's in a
+    /// directory inside a repository and it has been committed.
+    pub fn git_commit(&self) -> Option<GitCommit> {
+        self.dir.and_then(|d| d.git_commit(&self.name))
+    }
+
+    fn git_commit_hash(&self, colours: &Colours) -> Cell {
+        match self.git_commit() {
+            Some(c) => Cell::paint(colours.git.commit, &c.hash),
+            None    => Cell::paint(colours.punctuation, "-"),
+        }
+    }
+
+    fn git_commit_author(&self, colours: &Colours) -> Cell {
+        match self.git_commit() {
+            Some(c) => Cell::paint(colours.users.user_someone_else, &c.author),
+            None    => Cell::paint(colours.punctuation, "-"),
+        }
+    }
+
+    /// The repository whose working directory this is, if this is a
+    /// directory at the root of one.
+    pub fn git_repo(&self) -> Option<Repo> {
+        if self.is_directory() { Repo::at(&self.path) } else { None }
+    }
+
+    /// Whether this directory is a repository or a submodule, followed by
+    /// where its HEAD is, such as `repo [master, dirty]`.
+    pub fn git_repo_cell(&self, colours: &Colours) -> Cell {
+        let repo = match self.git_repo() {
+            Some(r) => r,
+            None    => return Cell::paint(colours.punctuation, "-"),
         };
 
-        Cell { text: status, length: 2 }
+        let kind = if repo.submodule { "submodule" } else { "repo" };
+
+        match repo.head {
+            Some(ref head) => {
+                let summary = git_head_view(head, colours);
+                Cell {
+                    text:   format!("{} {}", colours.git.branch.paint(kind), summary.text),
+                    length: kind.len() + 1 + summary.length,
+                }
+            },
+            None => Cell::paint(colours.git.branch, kind),
+        }
+    }
+
+    fn git_commit_date(&self, now: i64, colours: &Colours) -> Cell {
+        match self.git_commit() {
+            Some(c) => Cell::paint(colours.date, &relative_time(now - c.time)),
+            None    => Cell::paint(colours.punctuation, "-"),
+        }
+    }
+}
+
+/// The coloured pair of characters to display for a file's status in both
+/// of Git's areas.
+fn git_statuses_cell(statuses: GitStatuses, colours: &Colours) -> Cell {
+    let chars = [ git_status_char(statuses.staged, colours), git_status_char(statuses.unstaged, colours) ];
+    Cell { text: ANSIStrings(&chars).to_string(), length: 2 }
+}
+
+/// The coloured character to display for a file's status in one of Git's
+/// two areas.
+fn git_status_char(status: feature::GitStatus, colours: &Colours) -> ANSIString<'static> {
+    use feature::GitStatus::*;
+
+    match status {
+        NotModified => colours.punctuation.paint("-"),
+        New         => colours.git.new.paint("A"),
+        Modified    => colours.git.modified.paint("M"),
+        Deleted     => colours.git.deleted.paint("D"),
+        Renamed     => colours.git.renamed.paint("R"),
+        TypeChange  => colours.git.typechange.paint("T"),
+        Ignored     => colours.git.ignored.paint("I"),
+        Conflicted  => colours.git.conflicted.paint("U"),
     }
 }
 
+/// Describe how long ago something happened, given the number of seconds
+/// since it did, such as "3 days ago", using the largest unit that fits.
+/// Something in the future, with a negative number of seconds, gets
+/// described the other way round, such as "in 3 days".
+fn relative_time(seconds: i64) -> String {
+    static UNITS: &'static [(i64, &'static str)] = &[
+        (60 * 60 * 24 * 365, "year"),
+        (60 * 60 * 24 * 30,  "month"),
+        (60 * 60 * 24 * 7,   "week"),
+        (60 * 60 * 24,       "day"),
+        (60 * 60,            "hour"),
+        (60,                 "minute"),
+    ];
+
+    for &(length, name) in UNITS.iter() {
+        let count = seconds.abs() / length;
+        if count == 0 {
+            continue;
+        }
+
+        let amount = if count == 1 { format!("1 {}", name) }
+                              else { format!("{} {}s", count, name) };
+
+        if seconds < 0 { return format!("in {}", amount) }
+                  else { return format!("{} ago", amount) }
+    }
+
+    "just now".to_string()
+}
+
 /// Extract the filename to display from a path, converting it from UTF-8
 /// lossily, into a String.
 ///
@@ -502,6 +954,29 @@ fn path_filename(path: &Path) -> String {
     }
 }
 
+/// Split a device ID into its major and minor numbers. This uses the same
+/// encoding as glibc's `major` and `minor` macros.
+#[cfg(target_os = "linux")]
+fn device_numbers(rdev: u64) -> (u64, u64) {
+    let major = ((rdev >> 8) & 0xfff) | ((rdev >> 32) & !0xfff);
+    let minor = (rdev & 0xff) | ((rdev >> 12) & 0xffffff00);
+    (major, minor)
+}
+
+/// Split a device ID into its major and minor numbers, which on OS X are
+/// the top eight bits and the bottom twenty-four.
+#[cfg(target_os = "macos")]
+fn device_numbers(rdev: u64) -> (u64, u64) {
+    ((rdev >> 24) & 0xff, rdev & 0xffffff)
+}
+
+/// Split a device ID into its major and minor numbers, using the
+/// traditional sixteen-bit encoding.
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn device_numbers(rdev: u64) -> (u64, u64) {
+    ((rdev >> 8) & 0xff, rdev & 0xff)
+}
+
 /// Extract an extension from a string, if one is present, in lowercase.
 ///
 /// The extension is the series of characters after the last dot. This
@@ -519,6 +994,7 @@ pub mod test {
     pub use super::*;
 
     pub use column::{Cell, Column};
+    pub use output::Colours;
     pub use output::details::UserLocale;
 
     pub use users::{User, Group};
@@ -543,7 +1019,7 @@ pub mod test {
     }
 
     pub fn new_file(stat: io::FileStat, path: &'static str) -> File {
-        File::with_stat(stat, &Path::new(path), None, false)
+        File::with_stat(stat, &Path::new(path), None, false, &GitCache::new())
     }
 
     pub fn dummy_stat() -> io::FileStat {
@@ -587,7 +1063,7 @@ pub mod test {
             users.add_user(User { uid: 1000, name: "enoch".to_string(), primary_group: 100 });
 
             let cell = Cell::paint(Yellow.bold(), "enoch");
-            assert_eq!(cell, file.display(&Column::User, &mut users, &dummy_locale()))
+            assert_eq!(cell, file.display(&Column::User, &Colours::colourful(), &mut users, &dummy_locale()))
         }
 
         #[test]
@@ -600,7 +1076,7 @@ pub mod test {
             let mut users = MockUsers::with_current_uid(1000);
 
             let cell = Cell::paint(Yellow.bold(), "1000");
-            assert_eq!(cell, file.display(&Column::User, &mut users, &dummy_locale()))
+            assert_eq!(cell, file.display(&Column::User, &Colours::colourful(), &mut users, &dummy_locale()))
         }
 
         #[test]
@@ -614,7 +1090,7 @@ pub mod test {
             users.add_user(User { uid: 1000, name: "enoch".to_string(), primary_group: 100 });
 
             let cell = Cell::paint(Plain, "enoch");
-            assert_eq!(cell, file.display(&Column::User, &mut users, &dummy_locale()))
+            assert_eq!(cell, file.display(&Column::User, &Colours::colourful(), &mut users, &dummy_locale()))
         }
 
         #[test]
@@ -627,7 +1103,7 @@ pub mod test {
             let mut users = MockUsers::with_current_uid(3);
 
             let cell = Cell::paint(Plain, "1000");
-            assert_eq!(cell, file.display(&Column::User, &mut users, &dummy_locale()))
+            assert_eq!(cell, file.display(&Column::User, &Colours::colourful(), &mut users, &dummy_locale()))
         }
 
         #[test]
@@ -640,7 +1116,7 @@ pub mod test {
             let mut users = MockUsers::with_current_uid(3);
 
             let cell = Cell::paint(Plain, "2147483648");
-            assert_eq!(cell, file.display(&Column::User, &mut users, &dummy_locale()))
+            assert_eq!(cell, file.display(&Column::User, &Colours::colourful(), &mut users, &dummy_locale()))
         }
     }
 
@@ -658,7 +1134,7 @@ pub mod test {
             users.add_group(Group { gid: 100, name: "folk".to_string(), members: vec![] });
 
             let cell = Cell::paint(Plain, "folk");
-            assert_eq!(cell, file.display(&Column::Group, &mut users, &dummy_locale()))
+            assert_eq!(cell, file.display(&Column::Group, &Colours::colourful(), &mut users, &dummy_locale()))
         }
 
         #[test]
@@ -671,7 +1147,7 @@ pub mod test {
             let mut users = MockUsers::with_current_uid(3);
 
             let cell = Cell::paint(Plain, "100");
-            assert_eq!(cell, file.display(&Column::Group, &mut users, &dummy_locale()))
+            assert_eq!(cell, file.display(&Column::Group, &Colours::colourful(), &mut users, &dummy_locale()))
         }
 
         #[test]
@@ -686,7 +1162,7 @@ pub mod test {
             users.add_group(Group { gid: 100, name: "folk".to_string(), members: vec![] });
 
             let cell = Cell::paint(Yellow.bold(), "folk");
-            assert_eq!(cell, file.display(&Column::Group, &mut users, &dummy_locale()))
+            assert_eq!(cell, file.display(&Column::Group, &Colours::colourful(), &mut users, &dummy_locale()))
         }
 
         #[test]
@@ -701,7 +1177,7 @@ pub mod test {
             users.add_group(Group { gid: 100, name: "folk".to_string(), members: vec![ "eve".to_string() ] });
 
             let cell = Cell::paint(Yellow.bold(), "folk");
-            assert_eq!(cell, file.display(&Column::Group, &mut users, &dummy_locale()))
+            assert_eq!(cell, file.display(&Column::Group, &Colours::colourful(), &mut users, &dummy_locale()))
         }
 
         #[test]
@@ -714,7 +1190,218 @@ pub mod test {
             let mut users = MockUsers::with_current_uid(3);
 
             let cell = Cell::paint(Plain, "2147483648");
-            assert_eq!(cell, file.display(&Column::Group, &mut users, &dummy_locale()))
+            assert_eq!(cell, file.display(&Column::Group, &Colours::colourful(), &mut users, &dummy_locale()))
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{File, SizeCache, TotalSize, device_numbers, relative_time};
+    use std::env::temp_dir;
+    use std::fs;
+    use std::io::Write;
+    use std::os::unix::fs::MetadataExt;
+    use std::os::unix::raw::mode_t;
+    use std::path::{Path, PathBuf};
+    use output::Colours;
+    use ansi_term::Style::Plain;
+
+    fn execute_bit(bits: mode_t, bit: mode_t, special_bit: mode_t) -> String {
+        File::execute_bit(bits, bit, Plain, special_bit, ("s", "S"), Plain, &Colours::plain()).to_string()
+    }
+
+    fn sticky_bit(bits: mode_t) -> String {
+        File::execute_bit(bits, 0o001, Plain, 0o1000, ("t", "T"), Plain, &Colours::plain()).to_string()
+    }
+
+    #[test]
+    fn setuid_executable() {
+        assert_eq!(execute_bit(0o4755, 0o100, 0o4000), "s")
+    }
+
+    #[test]
+    fn setuid_not_executable() {
+        assert_eq!(execute_bit(0o4644, 0o100, 0o4000), "S")
+    }
+
+    #[test]
+    fn setgid_executable() {
+        assert_eq!(execute_bit(0o2755, 0o010, 0o2000), "s")
+    }
+
+    #[test]
+    fn sticky_executable() {
+        assert_eq!(sticky_bit(0o1777), "t")
+    }
+
+    #[test]
+    fn sticky_not_executable() {
+        assert_eq!(sticky_bit(0o1776), "T")
+    }
+
+    #[test]
+    fn no_special_bit() {
+        assert_eq!(execute_bit(0o0755, 0o100, 0o4000), "x")
+    }
+
+    #[test]
+    fn no_execute_bit() {
+        assert_eq!(execute_bit(0o0644, 0o100, 0o4000), "-")
+    }
+
+    #[test]
+    fn relative_just_now() {
+        assert_eq!(relative_time(59), "just now")
+    }
+
+    #[test]
+    fn relative_one_hour() {
+        assert_eq!(relative_time(60 * 60), "1 hour ago")
+    }
+
+    #[test]
+    fn relative_days() {
+        assert_eq!(relative_time(60 * 60 * 24 * 3 + 5), "3 days ago")
+    }
+
+    #[test]
+    fn relative_years() {
+        assert_eq!(relative_time(60 * 60 * 24 * 365 * 2), "2 years ago")
+    }
+
+    #[test]
+    fn relative_future() {
+        assert_eq!(relative_time(-60 * 60 * 3), "in 3 hours")
+    }
+
+    #[test]
+    fn relative_future_one() {
+        assert_eq!(relative_time(-60 * 60 * 24 * 7), "in 1 week")
+    }
+
+    #[test]
+    fn relative_near_future() {
+        assert_eq!(relative_time(-30), "just now")
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn small_device_numbers() {
+        assert_eq!(device_numbers(0x0801), (8, 1))
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn large_device_numbers() {
+        // Major 259 and minor 65536, which need the extended encoding.
+        assert_eq!(device_numbers(0x10010300), (259, 65536))
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn high_major_device_numbers() {
+        // makedev(0x1234, 5), whose major has bits above the first twelve,
+        // which mustn't end up in the minor.
+        assert_eq!(device_numbers(0x0000100000023405), (0x1234, 5))
+    }
+
+    #[test]
+    #[cfg(target_os = "macos")]
+    fn device_numbers_macos() {
+        assert_eq!(device_numbers(0x01000003), (1, 3))
+    }
+
+    /// Make an empty directory to test with, named after the test.
+    fn test_dir(name: &str) -> PathBuf {
+        let path = temp_dir().join(format!("exa-test-{}", name));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        path
+    }
+
+    fn write(path: &Path, contents: &[u8]) {
+        fs::File::create(path).unwrap().write_all(contents).unwrap();
+    }
+
+    fn total(cache: &SizeCache, path: &Path) -> TotalSize {
+        cache.total_size(path, &fs::symlink_metadata(path).unwrap())
+    }
+
+    fn own_size(path: &Path) -> u64 {
+        fs::symlink_metadata(path).unwrap().len()
+    }
+
+    #[test]
+    fn total_size() {
+        let dir = test_dir("total-size");
+        fs::create_dir(dir.join("sub")).unwrap();
+        write(&dir.join("one"), b"hello");
+        write(&dir.join("sub/two"), b"abc");
+
+        let expected = own_size(&dir) + own_size(&dir.join("sub")) + 8;
+        assert_eq!(total(&SizeCache::new(), &dir).bytes, expected);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn total_size_hard_links() {
+        let dir = test_dir("total-size-hard-links");
+        write(&dir.join("one"), b"hello");
+        fs::hard_link(dir.join("one"), dir.join("two")).unwrap();
+
+        let expected = own_size(&dir) + 5;
+        assert_eq!(total(&SizeCache::new(), &dir).bytes, expected);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn total_size_hard_links_in_siblings() {
+        let dir = test_dir("total-size-siblings");
+        fs::create_dir(dir.join("a")).unwrap();
+        fs::create_dir(dir.join("b")).unwrap();
+        write(&dir.join("a/one"), b"hello");
+        fs::hard_link(dir.join("a/one"), dir.join("b/two")).unwrap();
+
+        // The file only counts towards one of the two directories.
+        let cache = SizeCache::new();
+        let a = total(&cache, &dir.join("a")).bytes - own_size(&dir.join("a"));
+        let b = total(&cache, &dir.join("b")).bytes - own_size(&dir.join("b"));
+        assert_eq!(a + b, 5);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn total_size_reuses_subdirectories() {
+        let dir = test_dir("total-size-reuse");
+        fs::create_dir(dir.join("sub")).unwrap();
+        write(&dir.join("sub/one"), b"hello");
+
+        let cache = SizeCache::new();
+        let sub_before = total(&cache, &dir.join("sub"));
+        let parent = total(&cache, &dir);
+
+        // Adding to the subdirectory afterwards doesn't change its total,
+        // as it has already been read.
+        write(&dir.join("sub/two"), b"more");
+        assert_eq!(total(&cache, &dir.join("sub")), sub_before);
+        assert_eq!(parent.bytes, own_size(&dir) + sub_before.bytes);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn total_size_blocks() {
+        let dir = test_dir("total-size-blocks");
+        write(&dir.join("one"), b"hello");
+
+        let expected = fs::symlink_metadata(&dir).unwrap().as_raw().blocks() as u64
+                     + fs::symlink_metadata(&dir.join("one")).unwrap().as_raw().blocks() as u64;
+        assert_eq!(total(&SizeCache::new(), &dir).blocks, expected);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
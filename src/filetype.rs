@@ -1,13 +1,13 @@
-use file::{File, GREY};
+use file::File;
+use output::colours::FileTypes;
 use self::FileType::*;
 
 use ansi_term::Style;
-use ansi_term::Style::Plain;
-use ansi_term::Colour::{Red, Green, Yellow, Blue, Cyan, Fixed};
 
 #[derive(PartialEq, Debug)]
 pub enum FileType {
-    Normal, Directory, Executable, Immediate, Compiled, Symlink, Special,
+    Normal, Directory, Executable, Immediate, Compiled, Symlink,
+    Pipe, Socket, BlockDevice, CharDevice, Special,
     Image, Video, Music, Lossless, Compressed, Document, Temp, Crypto,
 }
 
@@ -53,23 +53,27 @@ static BUILD_TYPES: &'static [&'static str] = &[
 impl FileType {
 
     /// Get the `ansi_term::Style` that a file of this type should use.
-    pub fn style(&self) -> Style {
+    pub fn style(&self, colours: &FileTypes) -> Style {
         match *self {
-            Normal     => Plain,
-            Directory  => Blue.bold(),
-            Symlink    => Cyan.normal(),
-            Special    => Yellow.normal(),
-            Executable => Green.bold(),
-            Image      => Fixed(133).normal(),
-            Video      => Fixed(135).normal(),
-            Music      => Fixed(92).normal(),
-            Lossless   => Fixed(93).normal(),
-            Crypto     => Fixed(109).normal(),
-            Document   => Fixed(105).normal(),
-            Compressed => Red.normal(),
-            Temp       => GREY.normal(),
-            Immediate  => Yellow.bold().underline(),
-            Compiled   => Fixed(137).normal(),
+            Normal     => colours.normal,
+            Directory  => colours.directory,
+            Symlink    => colours.symlink,
+            Pipe       => colours.pipe,
+            Socket     => colours.socket,
+            BlockDevice => colours.block_device,
+            CharDevice  => colours.char_device,
+            Special    => colours.special,
+            Executable => colours.executable,
+            Image      => colours.image,
+            Video      => colours.video,
+            Music      => colours.music,
+            Lossless   => colours.lossless,
+            Crypto     => colours.crypto,
+            Document   => colours.document,
+            Compressed => colours.compressed,
+            Temp       => colours.temp,
+            Immediate  => colours.immediate,
+            Compiled   => colours.compiled,
         }
     }
 }
@@ -91,6 +95,18 @@ impl<'a> HasType for File<'a> {
         else if self.is_link() {
             return Symlink;
         }
+        else if self.is_pipe() {
+            return Pipe;
+        }
+        else if self.is_socket() {
+            return Socket;
+        }
+        else if self.is_block_device() {
+            return BlockDevice;
+        }
+        else if self.is_char_device() {
+            return CharDevice;
+        }
         else if !self.is_file() {
             return Special;
         }
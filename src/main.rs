@@ -1,9 +1,11 @@
 #![feature(collections, convert, core, exit_status, file_type, fs_ext, fs_mode)]
 #![feature(libc, metadata_ext, raw_ext, scoped, symlink_metadata)]
+#![cfg_attr(test, feature(test))]
 
 extern crate ansi_term;
 extern crate datetime;
 extern crate getopts;
+extern crate glob;
 extern crate locale;
 extern crate natord;
 extern crate num_cpus;
@@ -16,18 +18,25 @@ extern crate unicode_width;
 #[cfg(feature="git")]
 extern crate git2;
 
+#[cfg(test)]
+extern crate test;
+
+use std::cell::Cell;
 use std::env;
 use std::fs;
 use std::path::{Component, Path, PathBuf};
 use std::sync::mpsc::{channel, sync_channel};
 use std::thread;
 
+use config::Config;
 use dir::Dir;
-use file::File;
+use feature::GitCache;
+use file::{File, SizeCache};
 use options::{Options, View};
-use output::lines_view;
+use output::git_head_view;
 
 mod column;
+mod config;
 mod dir;
 mod feature;
 mod file;
@@ -35,6 +44,7 @@ mod filetype;
 mod options;
 mod output;
 mod term;
+mod time;
 
 #[cfg(not(test))]
 struct Exa<'a> {
@@ -42,16 +52,33 @@ struct Exa<'a> {
     options: Options,
     dirs:    Vec<PathBuf>,
     files:   Vec<File<'a>>,
+
+    /// The Git repositories that have been scanned so far, which get shared
+    /// between every directory in the run.
+    git_cache: GitCache,
+
+    /// The total sizes of the directories that have been added up so far,
+    /// which also get shared between every directory in the run.
+    size_cache: SizeCache,
+
+    /// Whether any entries have been printed yet by a machine-readable
+    /// view, which needs to separate entries from different listings.
+    printed_any: Cell<bool>,
 }
 
 #[cfg(not(test))]
 impl<'a> Exa<'a> {
     fn new(options: Options) -> Exa<'a> {
+        let git_cache = GitCache::with_ref(options.git_ref.clone());
+
         Exa {
             count: 0,
             options: options,
             dirs: Vec::new(),
             files: Vec::new(),
+            git_cache: git_cache,
+            size_cache: SizeCache::new(),
+            printed_any: Cell::new(false),
         }
     }
 
@@ -62,6 +89,7 @@ impl<'a> Exa<'a> {
 
         let is_tree = self.options.dir_action.is_tree() || self.options.dir_action.is_as_file();
         let total_files = files.len();
+        let git_cache = self.git_cache.clone();
 
         // Denotes the maxinum number of concurrent threads
         let (thread_capacity_tx, thread_capacity_rs) = sync_channel(8 * num_cpus::get());
@@ -98,6 +126,7 @@ impl<'a> Exa<'a> {
         for file in files.iter() {
             let file = file.clone();
             let results_tx = results_tx.clone();
+            let git_cache = git_cache.clone();
 
             // Block until there is room for another thread
             let _ = thread_capacity_tx.send(());
@@ -108,10 +137,10 @@ impl<'a> Exa<'a> {
                 let _ = results_tx.send(match fs::metadata(&path) {
                     Ok(stat) => {
                         if !stat.is_dir() {
-                            StatResult::File(File::with_stat(stat, &path, None, false))
+                            StatResult::File(File::with_stat(stat, &path, None, false, &git_cache))
                         }
                         else if is_tree {
-                            StatResult::File(File::with_stat(stat, &path, None, true))
+                            StatResult::File(File::with_stat(stat, &path, None, true, &git_cache))
                         }
                         else {
                             StatResult::Path(path.to_path_buf())
@@ -126,6 +155,24 @@ impl<'a> Exa<'a> {
         }
     }
 
+    fn calculate_total_sizes(&mut self) {
+        self.options.filter.calculate_total_sizes(&mut self.files, &self.size_cache);
+    }
+
+    fn print_start(&self) {
+        match self.options.view {
+            View::JSON(ref j) => j.start(),
+            View::CSV(ref c)  => c.start(),
+            _                 => {},
+        }
+    }
+
+    fn print_end(&self) {
+        if let View::JSON(ref j) = self.options.view {
+            j.end(self.printed_any.get());
+        }
+    }
+
     fn print_files(&self) {
         if !self.files.is_empty() {
             self.print(None, &self.files[..]);
@@ -145,17 +192,17 @@ impl<'a> Exa<'a> {
 
             // Put a gap between directories, or between the list of files and the
             // first directory.
-            if first {
+            if first || self.options.view.is_machine_readable() {
                 first = false;
             }
             else {
                 print!("\n");
             }
 
-            match Dir::readdir(&dir_path) {
+            match Dir::readdir(&dir_path, &self.git_cache) {
                 Ok(ref dir) => {
-                    let mut files = dir.files(false);
-                    self.options.transform_files(&mut files);
+                    let mut files = dir.files(false, &self.options.filter);
+                    self.options.transform_files(&mut files, &self.size_cache);
 
                     // When recursing, add any directories to the dirs stack
                     // backwards: the *last* element of the stack is used each
@@ -170,8 +217,14 @@ impl<'a> Exa<'a> {
                         }
                     }
 
-                    if self.count > 1 {
-                        println!("{}:", dir_path.display());
+                    if self.count > 1 && !self.options.view.is_machine_readable() {
+                        let head = if self.options.view.shows_git_status(dir) { dir.git_head() }
+                                                                          else { None };
+
+                        match head {
+                            Some(head) => println!("{}: {}", dir_path.display(), git_head_view(head, &self.options.colours).text),
+                            None       => println!("{}:", dir_path.display()),
+                        }
                     }
                     self.count += 1;
 
@@ -187,9 +240,11 @@ impl<'a> Exa<'a> {
 
     fn print(&self, dir: Option<&Dir>, files: &[File]) {
         match self.options.view {
-            View::Grid(g)     => g.view(files),
-            View::Details(d)  => d.view(dir, files),
-            View::Lines       => lines_view(files),
+            View::Grid(g)        => g.view(files, &self.options.colours),
+            View::Details(ref d) => d.view(dir, files, &self.options.colours, &self.size_cache),
+            View::Lines(l)       => l.view(files, &self.options.colours),
+            View::JSON(ref j)    => j.view(files, &self.printed_any, &self.size_cache),
+            View::CSV(ref c)     => c.view(files, &self.size_cache),
         }
     }
 }
@@ -198,12 +253,24 @@ impl<'a> Exa<'a> {
 fn main() {
     let args: Vec<String> = env::args().collect();
 
-    match Options::getopts(args.tail()) {
+    let config = match Config::load() {
+        Ok(c) => c,
+        Err(e) => {
+            println!("{}", e);
+            env::set_exit_status(e.error_code());
+            return;
+        },
+    };
+
+    match Options::getopts_with_config(args.tail(), config.as_ref()) {
         Ok((options, paths)) => {
             let mut exa = Exa::new(options);
             exa.load(&paths);
+            exa.calculate_total_sizes();
+            exa.print_start();
             exa.print_files();
             exa.print_dirs();
+            exa.print_end();
         },
         Err(e) => {
             println!("{}", e);
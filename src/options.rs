@@ -1,84 +1,122 @@
+use config::Config;
 use dir::Dir;
-use file::File;
+use file::{File, SizeCache};
 use column::Column;
 use column::Column::*;
-use feature::Attribute;
-use output::{Grid, Details};
-use term::dimensions;
+use feature::{Attribute, GitCache};
+use feature::InodeFlags as Flags;
+use output::{CSV, Colours, Grid, Details, JSON, Lines};
+use term::{dimensions, stdout_is_terminal};
+use time::seconds_now;
 
 use std::cmp::Ordering;
+use std::env;
 use std::fmt;
 use std::num::ParseIntError;
+use std::path::{Path, PathBuf};
 use std::os::unix::fs::MetadataExt;
 
 use getopts;
+use glob;
 use natord;
 
-use datetime::local::{LocalDateTime, DatePiece};
-
 use self::Misfire::*;
 
 /// The *Options* struct represents a parsed version of the user's
 /// command-line options.
-#[derive(PartialEq, Debug, Copy, Clone)]
+#[derive(PartialEq, Debug, Clone)]
 pub struct Options {
     pub dir_action: DirAction,
     pub filter: FileFilter,
     pub view: View,
+    pub colours: Colours,
+
+    /// The branch, tag, or commit to compare files' Git statuses against,
+    /// instead of HEAD.
+    pub git_ref: Option<String>,
 }
 
-#[derive(PartialEq, Debug, Copy, Clone)]
+#[derive(PartialEq, Debug, Clone)]
 pub struct FileFilter {
     list_dirs_first: bool,
     reverse: bool,
     show_invisibles: bool,
     sort_field: SortField,
+    ignore_patterns: IgnorePatterns,
+    git_ignore: bool,
+    total_size: bool,
 }
 
-#[derive(PartialEq, Debug, Copy, Clone)]
+#[derive(PartialEq, Debug, Clone)]
 pub enum View {
     Details(Details),
-    Lines,
+    Lines(Lines),
     Grid(Grid),
+    JSON(JSON),
+    CSV(CSV),
 }
 
 impl Options {
 
     /// Call getopts on the given slice of command-line strings.
     pub fn getopts(args: &[String]) -> Result<(Options, Vec<String>), Misfire> {
+        Options::getopts_with_config(args, None)
+    }
+
+    /// Call getopts on the given slice of command-line strings, using the
+    /// options in the configuration file, if there is one, as defaults.
+    pub fn getopts_with_config(args: &[String], config: Option<&Config>) -> Result<(Options, Vec<String>), Misfire> {
         let mut opts = getopts::Options::new();
         opts.optflag("1", "oneline",   "display one entry per line");
         opts.optflag("a", "all",       "show dot-files");
         opts.optflag("b", "binary",    "use binary prefixes in file sizes");
         opts.optflag("B", "bytes",     "list file sizes in bytes, without prefixes");
+        opts.optopt ("",  "colour",    "when to use terminal colours (always, auto, never)", "WHEN");
+        opts.optopt ("",  "color",     "when to use terminal colors (always, auto, never)", "WHEN");
         opts.optflag("d", "list-dirs", "list directories as regular files");
         opts.optflag("g", "group",     "show group as well as user");
+        opts.optopt ("",  "format",    "print in a machine-readable format (json, ndjson, csv, tsv)", "WORD");
         opts.optflag("",  "group-directories-first", "list directories before other files");
         opts.optflag("h", "header",    "show a header row at the top");
         opts.optflag("H", "links",     "show number of hard links");
+        opts.optmulti("I", "ignore-glob", "glob patterns (pipe-separated) of files to ignore", "GLOBS");
         opts.optflag("i", "inode",     "show each file's inode number");
         opts.optflag("l", "long",      "display extended details and attributes");
         opts.optopt ("L", "level",     "maximum depth of recursion", "DEPTH");
         opts.optflag("m", "modified",  "display timestamp of most recent modification");
+        opts.optflag("o", "octal-permissions", "show each file's permission bits as an octal number");
         opts.optflag("r", "reverse",   "reverse order of files");
         opts.optflag("R", "recurse",   "recurse into directories");
         opts.optopt ("s", "sort",      "field to sort by", "WORD");
         opts.optflag("S", "blocks",    "show number of file system blocks");
-        opts.optopt ("t", "time",      "which timestamp to show for a file", "WORD");
+        opts.optopt ("t", "time",      "which timestamps to show for a file, separated by commas, or all", "WORDS");
+        opts.optopt ("",  "time-style", "how to format timestamps (default, iso, long-iso, full-iso, relative, or +FORMAT)", "STYLE");
+        opts.optflag("",  "total-size", "show the total size of directories' contents");
         opts.optflag("T", "tree",      "recurse into subdirectories in a tree view");
         opts.optflag("u", "accessed",  "display timestamp of last access for a file");
         opts.optflag("U", "created",   "display timestamp of creation for a file");
+        opts.optflag("",  "changed",   "display timestamp of the most recent change to a file's inode");
         opts.optflag("x", "across",    "sort multi-column view entries across");
+        opts.optflag("Z", "context",   "show each file's SELinux security context");
 
         opts.optflag("",  "version",   "display version of exa");
         opts.optflag("?", "help",      "show list of command-line options");
 
         if cfg!(feature="git") {
             opts.optflag("", "git", "show git status");
+            opts.optflag("", "git-ignore", "hide files that are ignored by git");
+            opts.optflag("", "git-commit", "show the most recent commit to change each file");
+            opts.optflag("", "git-repos", "show which directories are git repositories");
+            opts.optopt ("", "git-ref", "compare git statuses against a branch, tag, or commit", "REF");
+        }
+
+        if Flags::feature_implemented() {
+            opts.optflag("",  "inode-flags", "show each file's inode flags, as set by chattr");
         }
 
         if Attribute::feature_implemented() {
-            opts.optflag("@", "extended", "display extended attribute keys and sizes in long (-l) output");
+            opts.optflag("",  "acl",      "list the entries of each file's POSIX ACLs in long (-l) output");
+            opts.optflag("@", "extended", "display extended attribute keys, sizes, and values in long (-l) output");
         }
 
         let matches = match opts.parse(args) {
@@ -86,6 +124,45 @@ impl Options {
             Err(e) => return Err(Misfire::InvalidOptions(e)),
         };
 
+        let config = match config {
+            Some(c) => c,
+            None    => return Options::deduce(&opts, &matches, None),
+        };
+
+        let mut defaults = try!(Options::config_defaults(&opts, &matches, config));
+
+        // An option from the configuration file can be useless for the view
+        // that gets picked, such as `group` without `--long`. The file is
+        // meant to hold defaults, not to make exa fail, so an option like
+        // this gets left out, and the rest of them get tried again.
+        loop {
+            let mut combined: Vec<String> = defaults.iter().flat_map(|d| d.1.iter().cloned()).collect();
+            combined.extend(args.iter().cloned());
+
+            let matches = match opts.parse(&combined) {
+                Ok(m) => m,
+                Err(e) => return Err(Misfire::InvalidOptions(e)),
+            };
+
+            let result = Options::deduce(&opts, &matches, Some(config));
+            let useless = match result {
+                Err(Misfire::Useless(name, _, _))  => name,
+                Err(Misfire::Useless2(name, _, _)) => name,
+                _                                  => return result,
+            };
+
+            // Options given on the command-line still get complained about.
+            if !defaults.iter().any(|d| d.0.opt_present(useless)) {
+                return result;
+            }
+
+            defaults.retain(|d| !d.0.opt_present(useless));
+        }
+    }
+
+    /// Turn the parsed command-line options into an Options object, along
+    /// with the paths to list.
+    fn deduce(opts: &getopts::Options, matches: &getopts::Matches, config: Option<&Config>) -> Result<(Options, Vec<String>), Misfire> {
         if matches.opt_present("help") {
             return Err(Misfire::Help(opts.usage("Usage:\n  exa [options] [files...]")));
         }
@@ -103,8 +180,16 @@ impl Options {
             reverse:         matches.opt_present("reverse"),
             show_invisibles: matches.opt_present("all"),
             sort_field:      sort_field,
+            ignore_patterns: try!(IgnorePatterns::deduce(&matches)),
+            git_ignore:      cfg!(feature="git") && matches.opt_present("git-ignore"),
+            total_size:      matches.opt_present("total-size"),
         };
 
+        let git_ref = if cfg!(feature="git") { matches.opt_str("git-ref") } else { None };
+        if git_ref.is_some() && !matches.opt_present("git") {
+            return Err(Misfire::Useless("git-ref", false, "git"));
+        }
+
         let path_strs = if matches.free.is_empty() {
             vec![ ".".to_string() ]
         }
@@ -112,33 +197,120 @@ impl Options {
             matches.free.clone()
         };
 
+        // A revision that doesn't exist would otherwise make the Git
+        // columns quietly disappear, so it gets checked up front.
+        if let Some(ref rev) = git_ref {
+            if path_strs.iter().any(|p| !GitCache::has_rev(Path::new(p), rev)) {
+                return Err(Misfire::BadGitRef(rev.clone()));
+            }
+        }
+
         let dir_action = try!(DirAction::deduce(&matches));
-        let view = try!(View::deduce(&matches, filter, dir_action));
+        let view = try!(View::deduce(&matches, &filter, dir_action));
+
+        let colours = if try!(TerminalColours::deduce(&matches)).should_paint() {
+            let config_colours = config.and_then(|c| c.colours.as_ref()).map(|c| &c[..]);
+            Colours::deduce(config_colours)
+        }
+        else {
+            Colours::plain()
+        };
 
         Ok((Options {
             dir_action: dir_action,
             view:       view,
             filter:     filter,
+            colours:    colours,
+            git_ref:    git_ref,
         }, path_strs))
     }
 
-    pub fn transform_files<'a>(&self, files: &mut Vec<File<'a>>) {
-        self.filter.transform_files(files)
+    /// Parse each option in the configuration file, leaving out any that
+    /// the command-line overrides, along with the arguments that it turns
+    /// into. Each option is checked on its own, so that an invalid one can
+    /// be reported along with its line number.
+    fn config_defaults(opts: &getopts::Options, given: &getopts::Matches, config: &Config) -> Result<Vec<(getopts::Matches, Vec<String>)>, Misfire> {
+        let mut defaults = Vec::new();
+
+        for line in config.lines.iter() {
+            let line_args = line.to_args();
+            let parsed = match opts.parse(&line_args) {
+                Ok(m) => m,
+                Err(e) => return Err(Misfire::BadConfig(config.path.clone(), line.number, e.to_string())),
+            };
+
+            if !parsed.free.is_empty() {
+                let message = format!("Option {} does not take an argument", line.name);
+                return Err(Misfire::BadConfig(config.path.clone(), line.number, message));
+            }
+
+            let overridden = given.opt_present(&line.name) || OVERRIDES.iter().any(|group| {
+                group.iter().any(|o| parsed.opt_present(o)) && group.iter().any(|o| given.opt_present(o))
+            });
+
+            if !overridden {
+                defaults.push((parsed, line_args));
+            }
+        }
+
+        Ok(defaults)
+    }
+
+    pub fn transform_files<'a>(&self, files: &mut Vec<File<'a>>, sizes: &SizeCache) {
+        self.filter.transform_files(files, sizes)
     }
 }
 
+/// Groups of options that all change the same setting. An option from the
+/// configuration file is left out when any option in the same group is
+/// given on the command-line, so the two don't conflict.
+static OVERRIDES: &'static [&'static [&'static str]] = &[
+    &[ "binary", "bytes" ],
+    &[ "colour", "color" ],
+    &[ "long", "oneline", "across" ],
+    &[ "recurse", "list-dirs", "tree" ],
+    &[ "time", "modified", "accessed", "created", "changed" ],
+];
+
 impl FileFilter {
+
+    /// Whether the file at the given path, in the given directory, should be
+    /// left out of the listing entirely. Unlike the rest of the filtering,
+    /// this gets checked before the file is even looked at, so ignored
+    /// directories never get read.
+    pub fn should_skip(&self, path: &Path, dir: &Dir) -> bool {
+        let glob_ignored = match path.file_name() {
+            Some(name) => self.ignore_patterns.is_ignored(&name.to_string_lossy()),
+            None       => false,
+        };
+
+        glob_ignored || (self.git_ignore && dir.is_git_ignored(path))
+    }
+
+    /// Work out the total sizes of any directories, if the user asked for
+    /// them, using the cache shared by the whole listing. This has to happen
+    /// before the files get sorted.
+    pub fn calculate_total_sizes<'a>(&self, files: &mut [File<'a>], sizes: &SizeCache) {
+        if self.total_size {
+            for file in files.iter_mut() {
+                file.calculate_total_size(sizes);
+            }
+        }
+    }
+
     /// Transform the files (sorting, reversing, filtering) before listing them.
-    pub fn transform_files<'a>(&self, files: &mut Vec<File<'a>>) {
+    pub fn transform_files<'a>(&self, files: &mut Vec<File<'a>>, sizes: &SizeCache) {
 
         if !self.show_invisibles {
             files.retain(|f| !f.is_dotfile());
         }
 
+        self.calculate_total_sizes(files, sizes);
+
         match self.sort_field {
             SortField::Unsorted => {},
             SortField::Name => files.sort_by(|a, b| natord::compare(&*a.name, &*b.name)),
-            SortField::Size => files.sort_by(|a, b| a.stat.len().cmp(&b.stat.len())),
+            SortField::Size => files.sort_by(|a, b| a.size().cmp(&b.size())),
             SortField::FileInode => files.sort_by(|a, b| a.stat.as_raw().ino().cmp(&b.stat.as_raw().ino())),
             SortField::Extension => files.sort_by(|a, b| match a.ext.cmp(&b.ext) {
                 Ordering::Equal => natord::compare(&*a.name, &*b.name),
@@ -146,7 +318,8 @@ impl FileFilter {
             }),
             SortField::ModifiedDate => files.sort_by(|a, b| a.stat.as_raw().mtime().cmp(&b.stat.as_raw().mtime())),
             SortField::AccessedDate => files.sort_by(|a, b| a.stat.as_raw().atime().cmp(&b.stat.as_raw().atime())),
-            SortField::CreatedDate  => files.sort_by(|a, b| a.stat.as_raw().ctime().cmp(&b.stat.as_raw().ctime())),
+            SortField::ChangedDate  => files.sort_by(|a, b| a.stat.as_raw().ctime().cmp(&b.stat.as_raw().ctime())),
+            SortField::CreatedDate  => files.sort_by(|a, b| a.birth_time().cmp(&b.birth_time())),
         }
 
         if self.reverse {
@@ -160,11 +333,43 @@ impl FileFilter {
     }
 }
 
+/// The **IgnorePatterns** are the glob patterns given with `--ignore-glob`.
+/// A file is ignored if its name matches any of them.
+#[derive(PartialEq, Debug, Clone)]
+pub struct IgnorePatterns {
+    patterns: Vec<glob::Pattern>,
+}
+
+impl IgnorePatterns {
+
+    /// Compile the patterns from every use of the `--ignore-glob` option,
+    /// each of which can hold several patterns separated by pipes.
+    fn deduce(matches: &getopts::Matches) -> Result<IgnorePatterns, Misfire> {
+        let mut patterns = Vec::new();
+
+        for input in matches.opt_strs("ignore-glob").iter() {
+            for word in input.split('|').filter(|w| !w.is_empty()) {
+                match glob::Pattern::new(word) {
+                    Ok(pattern) => patterns.push(pattern),
+                    Err(e)      => return Err(Misfire::FailedGlobPattern(word.to_string(), e.msg)),
+                }
+            }
+        }
+
+        Ok(IgnorePatterns { patterns: patterns })
+    }
+
+    /// Whether a file with the given name matches any of the patterns.
+    pub fn is_ignored(&self, file_name: &str) -> bool {
+        self.patterns.iter().any(|p| p.matches(file_name))
+    }
+}
+
 /// User-supplied field to sort by.
 #[derive(PartialEq, Debug, Copy, Clone)]
 pub enum SortField {
     Unsorted, Name, Extension, Size, FileInode,
-    ModifiedDate, AccessedDate, CreatedDate,
+    ModifiedDate, AccessedDate, ChangedDate, CreatedDate,
 }
 
 impl SortField {
@@ -177,6 +382,7 @@ impl SortField {
             "ext"  | "extension" => Ok(SortField::Extension),
             "mod"  | "modified"  => Ok(SortField::ModifiedDate),
             "acc"  | "accessed"  => Ok(SortField::AccessedDate),
+            "ch"   | "changed"   => Ok(SortField::ChangedDate),
             "cr"   | "created"   => Ok(SortField::CreatedDate),
             "none"               => Ok(SortField::Unsorted),
             "inode"              => Ok(SortField::FileInode),
@@ -217,6 +423,18 @@ pub enum Misfire {
 
     /// A numeric option was given that failed to be parsed as a number.
     FailedParse(ParseIntError),
+
+    /// One of the `--ignore-glob` patterns wasn't a valid glob, for the
+    /// given reason.
+    FailedGlobPattern(String, &'static str),
+
+    /// The configuration file couldn't be read, or the option on the given
+    /// line of it was invalid. Errors reading the file have a line of 0.
+    BadConfig(PathBuf, usize, String),
+
+    /// The revision given to `--git-ref` isn't in the repository of one of
+    /// the paths being listed.
+    BadGitRef(String),
 }
 
 impl Misfire {
@@ -238,13 +456,32 @@ impl fmt::Display for Misfire {
             Useless(a, true, b)   => write!(f, "Option --{} is useless given option --{}.", a, b),
             Useless2(a, b1, b2)   => write!(f, "Option --{} is useless without options --{} or --{}.", a, b1, b2),
             FailedParse(ref e)    => write!(f, "Failed to parse number: {}", e),
+            FailedGlobPattern(ref p, e) => write!(f, "Failed to parse glob pattern '{}': {}", p, e),
+            BadConfig(ref p, 0, ref e) => write!(f, "{}: {}", p.display(), e),
+            BadConfig(ref p, n, ref e) => write!(f, "{}:{}: {}", p.display(), n, e),
+            BadGitRef(ref r)      => write!(f, "Failed to find revision '{}' in the Git repository.", r),
         }
     }
 }
 
 impl View {
-    pub fn deduce(matches: &getopts::Matches, filter: FileFilter, dir_action: DirAction) -> Result<View, Misfire> {
-        if matches.opt_present("long") {
+    pub fn deduce(matches: &getopts::Matches, filter: &FileFilter, dir_action: DirAction) -> Result<View, Misfire> {
+        let lines = Lines {
+            git: cfg!(feature="git") && matches.opt_present("git"),
+        };
+
+        if let Some(word) = matches.opt_str("format") {
+            if matches.opt_present("across") {
+                Err(Misfire::Useless("across", true, "format"))
+            }
+            else if matches.opt_present("oneline") {
+                Err(Misfire::Useless("oneline", true, "format"))
+            }
+            else {
+                View::for_format(word, matches, filter, dir_action)
+            }
+        }
+        else if matches.opt_present("long") {
             if matches.opt_present("across") {
                 Err(Misfire::Useless("across", true, "long"))
             }
@@ -253,10 +490,11 @@ impl View {
             }
             else {
                 let details = Details {
-                        columns: try!(Columns::deduce(matches)),
+                        columns: try!(Columns::deduce(matches, filter.sort_field)),
                         header: matches.opt_present("header"),
-                        recurse: dir_action.recurse_options().map(|o| (o, filter)),
+                        recurse: dir_action.recurse_options().map(|o| (o, filter.clone())),
                         xattr: Attribute::feature_implemented() && matches.opt_present("extended"),
+                        acl: Attribute::feature_implemented() && matches.opt_present("acl"),
                 };
 
                 Ok(View::Details(details))
@@ -280,37 +518,59 @@ impl View {
         else if matches.opt_present("blocks") {
             Err(Misfire::Useless("blocks", false, "long"))
         }
-        else if cfg!(feature="git") && matches.opt_present("git") {
-            Err(Misfire::Useless("git", false, "long"))
+        else if cfg!(feature="git") && matches.opt_present("git-commit") {
+            Err(Misfire::Useless("git-commit", false, "long"))
+        }
+        else if cfg!(feature="git") && matches.opt_present("git-repos") {
+            Err(Misfire::Useless("git-repos", false, "long"))
         }
         else if matches.opt_present("time") {
             Err(Misfire::Useless("time", false, "long"))
         }
+        else if matches.opt_present("time-style") {
+            Err(Misfire::Useless("time-style", false, "long"))
+        }
         else if matches.opt_present("tree") {
             Err(Misfire::Useless("tree", false, "long"))
         }
         else if matches.opt_present("group") {
             Err(Misfire::Useless("group", false, "long"))
         }
+        else if matches.opt_present("context") {
+            Err(Misfire::Useless("context", false, "long"))
+        }
+        else if matches.opt_present("octal-permissions") {
+            Err(Misfire::Useless("octal-permissions", false, "long"))
+        }
+        else if matches.opt_present("total-size") {
+            Err(Misfire::Useless("total-size", false, "long"))
+        }
         else if matches.opt_present("level") && !matches.opt_present("recurse") {
             Err(Misfire::Useless2("level", "recurse", "tree"))
         }
         else if Attribute::feature_implemented() && matches.opt_present("extended") {
             Err(Misfire::Useless("extended", false, "long"))
         }
+        else if Flags::feature_implemented() && matches.opt_present("inode-flags") {
+            Err(Misfire::Useless("inode-flags", false, "long"))
+        }
+        else if Attribute::feature_implemented() && matches.opt_present("acl") {
+            Err(Misfire::Useless("acl", false, "long"))
+        }
         else if matches.opt_present("oneline") {
             if matches.opt_present("across") {
                 Err(Misfire::Useless("across", true, "oneline"))
             }
             else {
-                Ok(View::Lines)
+                Ok(View::Lines(lines))
             }
         }
         else {
             if let Some((width, _)) = dimensions() {
                 let grid = Grid {
                     across: matches.opt_present("across"),
-                    console_width: width
+                    console_width: width,
+                    git: lines.git,
                 };
 
                 Ok(View::Grid(grid))
@@ -319,10 +579,108 @@ impl View {
                 // If the terminal width couldn't be matched for some reason, such
                 // as the program's stdout being connected to a file, then
                 // fallback to the lines view.
-                Ok(View::Lines)
+                Ok(View::Lines(lines))
             }
         }
     }
+
+    /// Find which machine-readable view to use based on a user-supplied word.
+    fn for_format(word: String, matches: &getopts::Matches, filter: &FileFilter, dir_action: DirAction) -> Result<View, Misfire> {
+        let json = |newline_delimited| JSON {
+            newline_delimited: newline_delimited,
+            recurse: dir_action.recurse_options().map(|o| (o, filter.clone())),
+            git: cfg!(feature="git") && matches.opt_present("git"),
+        };
+
+        let csv = |separator| -> Result<View, Misfire> {
+            Ok(View::CSV(CSV {
+                columns: try!(Columns::deduce(matches, filter.sort_field)),
+                separator: separator,
+                recurse: dir_action.recurse_options().map(|o| (o, filter.clone())),
+            }))
+        };
+
+        match &word[..] {
+            "json"    => Ok(View::JSON(json(false))),
+            "ndjson"  => Ok(View::JSON(json(true))),
+            "csv"     => csv(','),
+            "tsv"     => csv('\t'),
+            format    => Err(Misfire::InvalidOptions(getopts::Fail::UnrecognizedOption(format!("--format {}", format)))),
+        }
+    }
+
+    /// Whether this view prints output for other programs to read, rather
+    /// than people. These views don't have anything else printed around
+    /// them, such as the names of directories.
+    pub fn is_machine_readable(&self) -> bool {
+        match *self {
+            View::JSON(_) => true,
+            View::CSV(_)  => true,
+            _             => false,
+        }
+    }
+
+    /// Whether this view shows the Git column when listing the given
+    /// directory, which is when where the repository's HEAD is gets shown
+    /// along with the directory's name.
+    pub fn shows_git_status(&self, dir: &Dir) -> bool {
+        match *self {
+            View::Details(ref d) => d.columns.for_dir(Some(dir)).contains(&GitStatus),
+            _                    => false,
+        }
+    }
+}
+
+/// When to use colours in the output.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum TerminalColours {
+
+    /// Always paint the output, even when it isn't going to a terminal.
+    Always,
+
+    /// Paint the output only if it's going to a terminal, and the
+    /// `NO_COLOR` environment variable isn't set.
+    Automatic,
+
+    /// Never paint the output.
+    Never,
+}
+
+impl TerminalColours {
+
+    /// Find which mode to use based on a user-supplied word, defaulting to
+    /// automatic if none was given.
+    fn deduce(matches: &getopts::Matches) -> Result<TerminalColours, Misfire> {
+        let word = match matches.opt_str("colour").or(matches.opt_str("color")) {
+            Some(w) => w,
+            None    => return Ok(TerminalColours::Automatic),
+        };
+
+        match &word[..] {
+            "always"             => Ok(TerminalColours::Always),
+            "auto" | "automatic" => Ok(TerminalColours::Automatic),
+            "never"              => Ok(TerminalColours::Never),
+            otherwise            => Err(Misfire::InvalidOptions(getopts::Fail::UnrecognizedOption(format!("--colour {}", otherwise)))),
+        }
+    }
+
+    /// Whether the output should be painted in this mode.
+    fn should_paint(&self) -> bool {
+        match *self {
+            TerminalColours::Always    => true,
+            TerminalColours::Never     => false,
+            TerminalColours::Automatic => stdout_is_terminal() && !no_color_set(),
+        }
+    }
+}
+
+/// Whether the `NO_COLOR` environment variable is set to anything, which
+/// asks programs not to use colour unless told to on the command-line.
+fn no_color_set() -> bool {
+    match env::var("NO_COLOR") {
+        Ok(value) => !value.is_empty(),
+        Err(_)    => false,
+    }
 }
 
 #[derive(PartialEq, Debug, Copy, Clone)]
@@ -350,6 +708,7 @@ impl SizeFormat {
 pub enum TimeType {
     FileAccessed,
     FileModified,
+    FileChanged,
     FileCreated,
 }
 
@@ -358,25 +717,89 @@ impl TimeType {
         match *self {
             TimeType::FileAccessed => "Date Accessed",
             TimeType::FileModified => "Date Modified",
+            TimeType::FileChanged  => "Date Changed",
             TimeType::FileCreated  => "Date Created",
         }
     }
 }
 
+/// How to format the timestamps in the time columns.
+#[derive(PartialEq, Debug, Clone)]
+pub enum TimeStyle {
+
+    /// The day and month, then the time for this year's timestamps, or the
+    /// year for older ones, like `ls` does.
+    Default,
+
+    /// The month and day, then the time for this year's timestamps, or the
+    /// full date for older ones, such as `06-14 09:30` or `2014-06-14`.
+    ISO,
+
+    /// The full date and time to the minute, such as `2015-06-14 09:30`.
+    LongISO,
+
+    /// The full date and time to the nanosecond, with the timezone offset,
+    /// such as `2015-06-14 09:30:12.345678901 +0100`.
+    FullISO,
+
+    /// How long ago the timestamp was, such as `3 hours ago`.
+    Relative,
+
+    /// A user-supplied `strftime` format, which can use `%N` for the
+    /// nanoseconds.
+    Custom(String),
+}
+
+impl TimeStyle {
+
+    /// Find which style to use based on a user-supplied word, which is a
+    /// format string if it starts with a plus.
+    fn deduce(matches: &getopts::Matches) -> Result<TimeStyle, Misfire> {
+        let word = match matches.opt_str("time-style") {
+            Some(w) => w,
+            None    => return Ok(TimeStyle::Default),
+        };
+
+        if word.starts_with("+") {
+            return Ok(TimeStyle::Custom(word[1..].to_string()));
+        }
+
+        match &word[..] {
+            "default"   => Ok(TimeStyle::Default),
+            "iso"       => Ok(TimeStyle::ISO),
+            "long-iso"  => Ok(TimeStyle::LongISO),
+            "full-iso"  => Ok(TimeStyle::FullISO),
+            "relative"  => Ok(TimeStyle::Relative),
+            style       => Err(TimeStyle::none(style)),
+        }
+    }
+
+    /// How to display an error when the word didn't match with anything.
+    fn none(style: &str) -> Misfire {
+        Misfire::InvalidOptions(getopts::Fail::UnrecognizedOption(format!("--time-style {}", style)))
+    }
+}
+
 #[derive(PartialEq, Debug, Copy, Clone)]
 pub struct TimeTypes {
     accessed: bool,
     modified: bool,
+    changed:  bool,
     created:  bool,
 }
 
 impl TimeTypes {
 
-    /// Find which field to use based on a user-supplied word.
-    fn deduce(matches: &getopts::Matches) -> Result<TimeTypes, Misfire> {
+    /// Find which fields to use based on a user-supplied list of words,
+    /// separated by commas, or the word `all` for every field. When no
+    /// fields are given, the modified time is shown, along with the time
+    /// being sorted by, if it's a different one, so the order of the
+    /// listing makes sense.
+    fn deduce(matches: &getopts::Matches, sort_field: SortField) -> Result<TimeTypes, Misfire> {
         let possible_word = matches.opt_str("time");
         let modified = matches.opt_present("modified");
         let created  = matches.opt_present("created");
+        let changed  = matches.opt_present("changed");
         let accessed = matches.opt_present("accessed");
 
         if let Some(word) = possible_word {
@@ -386,27 +809,52 @@ impl TimeTypes {
             else if created {
                 return Err(Misfire::Useless("created", true, "time"));
             }
+            else if changed {
+                return Err(Misfire::Useless("changed", true, "time"));
+            }
             else if accessed {
                 return Err(Misfire::Useless("accessed", true, "time"));
             }
 
-            match &word[..] {
-                "mod" | "modified"  => Ok(TimeTypes { accessed: false, modified: true, created: false }),
-                "acc" | "accessed"  => Ok(TimeTypes { accessed: true, modified: false, created: false }),
-                "cr"  | "created"   => Ok(TimeTypes { accessed: false, modified: false, created: true }),
-                field   => Err(TimeTypes::none(field)),
+            let mut types = TimeTypes { accessed: false, modified: false, changed: false, created: false };
+            for field in word.split(',') {
+                match field {
+                    "mod" | "modified"  => types.modified = true,
+                    "acc" | "accessed"  => types.accessed = true,
+                    "ch"  | "changed"   => types.changed  = true,
+                    "cr"  | "created"   => types.created  = true,
+                    "all"               => types = TimeTypes { accessed: true, modified: true, changed: true, created: true },
+                    field               => return Err(TimeTypes::none(field)),
+                }
             }
+
+            Ok(types)
+        }
+        else if modified || changed || created || accessed {
+            Ok(TimeTypes { accessed: accessed, modified: modified, changed: changed, created: created })
         }
         else {
-            if modified || created || accessed {
-                Ok(TimeTypes { accessed: accessed, modified: modified, created: created })
-            }
-            else {
-                Ok(TimeTypes { accessed: false, modified: true, created: false })
-            }
+            Ok(TimeTypes {
+                accessed: sort_field == SortField::AccessedDate,
+                modified: true,
+                changed:  sort_field == SortField::ChangedDate,
+                created:  sort_field == SortField::CreatedDate,
+            })
         }
     }
 
+    /// The time types to show, in the order their columns go in: the three
+    /// times from a file's stat information, in the order `stat` prints
+    /// them, followed by its creation time.
+    pub fn types(&self) -> Vec<TimeType> {
+        let mut types = Vec::new();
+        if self.accessed { types.push(TimeType::FileAccessed) }
+        if self.modified { types.push(TimeType::FileModified) }
+        if self.changed  { types.push(TimeType::FileChanged) }
+        if self.created  { types.push(TimeType::FileCreated) }
+        types
+    }
+
     /// How to display an error when the word didn't match with anything.
     fn none(field: &str) -> Misfire {
         Misfire::InvalidOptions(getopts::Fail::UnrecognizedOption(format!("--time {}", field)))
@@ -493,39 +941,75 @@ impl RecurseOptions {
     }
 }
 
-#[derive(PartialEq, Copy, Clone, Debug)]
+#[derive(PartialEq, Clone, Debug)]
 pub struct Columns {
     size_format: SizeFormat,
     time_types: TimeTypes,
+    time_style: TimeStyle,
     inode: bool,
     links: bool,
     blocks: bool,
     group: bool,
-    git: bool
+    context: bool,
+    octal: bool,
+    inode_flags: bool,
+    git: bool,
+    git_commit: bool,
+    git_repos: bool,
 }
 
 impl Columns {
-    pub fn deduce(matches: &getopts::Matches) -> Result<Columns, Misfire> {
+    pub fn deduce(matches: &getopts::Matches, sort_field: SortField) -> Result<Columns, Misfire> {
         Ok(Columns {
             size_format: try!(SizeFormat::deduce(matches)),
-            time_types:  try!(TimeTypes::deduce(matches)),
+            time_types:  try!(TimeTypes::deduce(matches, sort_field)),
+            time_style:  try!(TimeStyle::deduce(matches)),
             inode:  matches.opt_present("inode"),
             links:  matches.opt_present("links"),
             blocks: matches.opt_present("blocks"),
             group:  matches.opt_present("group"),
+            context: matches.opt_present("context"),
+            octal:  matches.opt_present("octal-permissions"),
+            inode_flags: Flags::feature_implemented() && matches.opt_present("inode-flags"),
             git:    cfg!(feature="git") && matches.opt_present("git"),
+            git_commit: cfg!(feature="git") && matches.opt_present("git-commit"),
+            git_repos:  cfg!(feature="git") && matches.opt_present("git-repos"),
         })
     }
 
     pub fn for_dir(&self, dir: Option<&Dir>) -> Vec<Column> {
+        let has_git_repo = match dir {
+            Some(d) => d.has_git_repo(),
+            None    => false,
+        };
+
+        self.collect(has_git_repo)
+    }
+
+    /// The columns to use for every directory in the run, which is what the
+    /// machine-readable views need, as their columns can't vary between
+    /// listings. The Git column is included whenever it's been asked for.
+    pub fn for_every_dir(&self) -> Vec<Column> {
+        self.collect(true)
+    }
+
+    fn collect(&self, has_git_repo: bool) -> Vec<Column> {
         let mut columns = vec![];
 
         if self.inode {
             columns.push(Inode);
         }
 
+        if self.octal {
+            columns.push(Octal);
+        }
+
         columns.push(Permissions);
 
+        if self.inode_flags {
+            columns.push(Column::InodeFlags);
+        }
+
         if self.links {
             columns.push(HardLinks);
         }
@@ -542,26 +1026,30 @@ impl Columns {
             columns.push(Group);
         }
 
-        let current_year = LocalDateTime::now().year();
+        if self.context {
+            columns.push(SecurityContext);
+        }
+
+        let now = seconds_now();
 
-        if self.time_types.modified {
-            columns.push(Timestamp(TimeType::FileModified, current_year));
+        for time_type in self.time_types.types().into_iter() {
+            columns.push(Timestamp(time_type, self.time_style.clone(), now));
         }
 
-        if self.time_types.created {
-            columns.push(Timestamp(TimeType::FileCreated, current_year));
+        if cfg!(feature="git") && self.git && has_git_repo {
+            columns.push(GitStatus);
         }
 
-        if self.time_types.accessed {
-            columns.push(Timestamp(TimeType::FileAccessed, current_year));
+        if cfg!(feature="git") && self.git_commit && has_git_repo {
+            columns.push(GitCommitHash);
+            columns.push(GitCommitAuthor);
+            columns.push(GitCommitDate(now));
         }
 
-        if cfg!(feature="git") {
-            if let Some(d) = dir {
-                if self.git && d.has_git_repo() {
-                    columns.push(GitStatus);
-                }
-            }
+        // Whether an entry is a repository has nothing to do with whether
+        // the directory it's in is one, so this column is always shown.
+        if cfg!(feature="git") && self.git_repos {
+            columns.push(GitRepo);
         }
 
         columns
@@ -570,10 +1058,14 @@ impl Columns {
 
 #[cfg(test)]
 mod test {
-    use super::Options;
+    use super::{Options, SortField, TimeStyle, TimeType, View};
     use super::Misfire;
     use super::Misfire::*;
+    use config::Config;
+    use output::{Colours, Lines};
+    use std::path::PathBuf;
     use feature::Attribute;
+    use feature::InodeFlags as Flags;
 
     fn is_helpful<T>(misfire: Result<T, Misfire>) -> bool {
         match misfire {
@@ -636,6 +1128,12 @@ mod test {
         assert_eq!(opts.unwrap_err(), Misfire::Useless("across", true, "oneline"))
     }
 
+    #[test]
+    fn just_context() {
+        let opts = Options::getopts(&[ "--context".to_string() ]);
+        assert_eq!(opts.unwrap_err(), Misfire::Useless("context", false, "long"))
+    }
+
     #[test]
     fn just_header() {
         let opts = Options::getopts(&[ "--header".to_string() ]);
@@ -648,6 +1146,12 @@ mod test {
         assert_eq!(opts.unwrap_err(), Misfire::Useless("group", false, "long"))
     }
 
+    #[test]
+    fn just_octal_permissions() {
+        let opts = Options::getopts(&[ "--octal-permissions".to_string() ]);
+        assert_eq!(opts.unwrap_err(), Misfire::Useless("octal-permissions", false, "long"))
+    }
+
     #[test]
     fn just_inode() {
         let opts = Options::getopts(&[ "--inode".to_string() ]);
@@ -668,9 +1172,9 @@ mod test {
 
     #[test]
     #[cfg(feature="git")]
-    fn just_git() {
-        let opts = Options::getopts(&[ "--git".to_string() ]);
-        assert_eq!(opts.unwrap_err(), Misfire::Useless("git", false, "long"))
+    fn git_oneline() {
+        let opts = Options::getopts(&[ "--git".to_string(), "--oneline".to_string() ]).unwrap().0;
+        assert_eq!(opts.view, View::Lines(Lines { git: true }))
     }
 
     #[test]
@@ -681,6 +1185,330 @@ mod test {
         }
     }
 
+    #[test]
+    fn inode_flags_without_long() {
+        if Flags::feature_implemented() {
+            let opts = Options::getopts(&[ "--inode-flags".to_string() ]);
+            assert_eq!(opts.unwrap_err(), Misfire::Useless("inode-flags", false, "long"))
+        }
+    }
+
+    #[test]
+    fn acl_without_long() {
+        if Attribute::feature_implemented() {
+            let opts = Options::getopts(&[ "--acl".to_string() ]);
+            assert_eq!(opts.unwrap_err(), Misfire::Useless("acl", false, "long"))
+        }
+    }
+
+    #[test]
+    fn format_across() {
+        let opts = Options::getopts(&[ "--format=json".to_string(), "--across".to_string() ]);
+        assert_eq!(opts.unwrap_err(), Misfire::Useless("across", true, "format"))
+    }
+
+    #[test]
+    fn unknown_format() {
+        let opts = Options::getopts(&[ "--format=yaml".to_string() ]);
+        assert!(opts.is_err())
+    }
+
+    #[test]
+    fn format_with_long_options() {
+        let opts = Options::getopts(&[ "--format=ndjson".to_string(), "--inode".to_string() ]);
+        assert!(opts.unwrap().0.view.is_machine_readable())
+    }
+
+    #[test]
+    fn csv_file_sizes() {
+        let opts = Options::getopts(&[ "--format=csv".to_string(), "--binary".to_string(), "--bytes".to_string() ]);
+        assert_eq!(opts.unwrap_err(), Misfire::Conflict("binary", "bytes"))
+    }
+
+    fn config(contents: &str) -> Config {
+        Config::parse(PathBuf::from("config"), contents).unwrap()
+    }
+
+    #[test]
+    fn config_defaults() {
+        let opts = Options::getopts_with_config(&[ "--long".to_string() ], Some(&config("group\nsort = size")));
+        let options = opts.unwrap().0;
+        assert_eq!(options.filter.sort_field, SortField::Size);
+        match options.view {
+            View::Details(d) => assert!(d.columns.group),
+            _                => panic!("Expected a details view"),
+        }
+    }
+
+    #[test]
+    fn config_long_only_without_long() {
+        let opts = Options::getopts_with_config(&[], Some(&config("group\nsort = size")));
+        let options = opts.unwrap().0;
+        assert_eq!(options.filter.sort_field, SortField::Size);
+        match options.view {
+            View::Details(_) => panic!("Unexpected details view"),
+            _                => {},
+        }
+    }
+
+    #[test]
+    fn config_long_only_with_long() {
+        let opts = Options::getopts_with_config(&[ "--long".to_string() ], Some(&config("header")));
+        match opts.unwrap().0.view {
+            View::Details(d) => assert!(d.header),
+            _                => panic!("Expected a details view"),
+        }
+    }
+
+    #[test]
+    fn useless_option_given_with_config() {
+        let opts = Options::getopts_with_config(&[ "--group".to_string() ], Some(&config("header")));
+        assert_eq!(opts.unwrap_err(), Misfire::Useless("group", false, "long"))
+    }
+
+    #[test]
+    fn config_overridden() {
+        let opts = Options::getopts_with_config(&[ "--sort=name".to_string() ], Some(&config("sort = size")));
+        assert_eq!(opts.unwrap().0.filter.sort_field, SortField::Name)
+    }
+
+    #[test]
+    fn config_overridden_by_group() {
+        let opts = Options::getopts_with_config(&[ "--long".to_string(), "--bytes".to_string() ], Some(&config("binary")));
+        assert!(opts.is_ok())
+    }
+
+    #[test]
+    fn config_invalid_option() {
+        let opts = Options::getopts_with_config(&[], Some(&config("# comment\n--wibble")));
+        match opts.unwrap_err() {
+            BadConfig(_, line, _) => assert_eq!(line, 2),
+            e                     => panic!("Unexpected error {:?}", e),
+        }
+    }
+
+    #[test]
+    fn config_unexpected_argument() {
+        let opts = Options::getopts_with_config(&[], Some(&config("group = yes")));
+        match opts.unwrap_err() {
+            BadConfig(_, line, _) => assert_eq!(line, 1),
+            e                     => panic!("Unexpected error {:?}", e),
+        }
+    }
+
+    #[test]
+    fn colour_never() {
+        let opts = Options::getopts(&[ "--colour=never".to_string() ]);
+        assert_eq!(opts.unwrap().0.colours, Colours::plain())
+    }
+
+    #[test]
+    fn color_never() {
+        let opts = Options::getopts(&[ "--color=never".to_string() ]);
+        assert_eq!(opts.unwrap().0.colours, Colours::plain())
+    }
+
+    #[test]
+    fn colour_always() {
+        let opts = Options::getopts(&[ "--colour=always".to_string() ]);
+        assert!(opts.unwrap().0.colours != Colours::plain())
+    }
+
+    #[test]
+    fn unknown_colour() {
+        let opts = Options::getopts(&[ "--colour=sometimes".to_string() ]);
+        assert!(opts.is_err())
+    }
+
+    #[test]
+    fn ignore_glob() {
+        let filter = Options::getopts(&[ "--ignore-glob=target|*.o".to_string(), "-I".to_string(), "node_modules".to_string() ]).unwrap().0.filter;
+        assert!(filter.ignore_patterns.is_ignored("target"));
+        assert!(filter.ignore_patterns.is_ignored("main.o"));
+        assert!(filter.ignore_patterns.is_ignored("node_modules"));
+        assert!(!filter.ignore_patterns.is_ignored("main.rs"))
+    }
+
+    #[test]
+    #[cfg(feature="git")]
+    fn git_ignore() {
+        let filter = Options::getopts(&[ "--git-ignore".to_string() ]).unwrap().0.filter;
+        assert!(filter.git_ignore)
+    }
+
+    #[test]
+    fn changed() {
+        let opts = Options::getopts(&[ "--long".to_string(), "--changed".to_string() ]).unwrap().0;
+        match opts.view {
+            View::Details(d) => assert!(d.columns.time_types.changed && !d.columns.time_types.modified),
+            _                => assert!(false),
+        }
+    }
+
+    #[test]
+    fn several_times() {
+        let opts = Options::getopts(&[ "--long".to_string(), "--time=mod,acc".to_string() ]).unwrap().0;
+        match opts.view {
+            View::Details(d) => assert_eq!(d.columns.time_types.types(), vec![ TimeType::FileAccessed, TimeType::FileModified ]),
+            _                => assert!(false),
+        }
+    }
+
+    #[test]
+    fn all_times() {
+        let opts = Options::getopts(&[ "--long".to_string(), "--time=all".to_string() ]).unwrap().0;
+        match opts.view {
+            View::Details(d) => assert_eq!(d.columns.time_types.types(), vec![ TimeType::FileAccessed, TimeType::FileModified,
+                                                                               TimeType::FileChanged, TimeType::FileCreated ]),
+            _                => assert!(false),
+        }
+    }
+
+    #[test]
+    fn bad_time_in_list() {
+        let opts = Options::getopts(&[ "--long".to_string(), "--time=mod,yesterday".to_string() ]);
+        assert!(opts.is_err())
+    }
+
+    #[test]
+    fn time_follows_sort() {
+        let opts = Options::getopts(&[ "--long".to_string(), "--sort=accessed".to_string() ]).unwrap().0;
+        match opts.view {
+            View::Details(d) => assert_eq!(d.columns.time_types.types(), vec![ TimeType::FileAccessed, TimeType::FileModified ]),
+            _                => assert!(false),
+        }
+    }
+
+    #[test]
+    fn time_follows_sort_created() {
+        let opts = Options::getopts(&[ "--long".to_string(), "--sort=created".to_string() ]).unwrap().0;
+        match opts.view {
+            View::Details(d) => assert_eq!(d.columns.time_types.types(), vec![ TimeType::FileModified, TimeType::FileCreated ]),
+            _                => assert!(false),
+        }
+    }
+
+    #[test]
+    fn time_default() {
+        let opts = Options::getopts(&[ "--long".to_string() ]).unwrap().0;
+        match opts.view {
+            View::Details(d) => assert_eq!(d.columns.time_types.types(), vec![ TimeType::FileModified ]),
+            _                => assert!(false),
+        }
+    }
+
+    #[test]
+    fn time_default_sorted_by_modified() {
+        let opts = Options::getopts(&[ "--long".to_string(), "--sort=modified".to_string() ]).unwrap().0;
+        match opts.view {
+            View::Details(d) => assert_eq!(d.columns.time_types.types(), vec![ TimeType::FileModified ]),
+            _                => assert!(false),
+        }
+    }
+
+    #[test]
+    fn time_style() {
+        let opts = Options::getopts(&[ "--long".to_string(), "--time-style=full-iso".to_string() ]).unwrap().0;
+        match opts.view {
+            View::Details(d) => assert_eq!(d.columns.time_style, TimeStyle::FullISO),
+            _                => assert!(false),
+        }
+    }
+
+    #[test]
+    fn custom_time_style() {
+        let opts = Options::getopts(&[ "--long".to_string(), "--time-style=+%H:%M:%S.%N".to_string() ]).unwrap().0;
+        match opts.view {
+            View::Details(d) => assert_eq!(d.columns.time_style, TimeStyle::Custom("%H:%M:%S.%N".to_string())),
+            _                => assert!(false),
+        }
+    }
+
+    #[test]
+    fn bad_time_style() {
+        let opts = Options::getopts(&[ "--long".to_string(), "--time-style=sundial".to_string() ]);
+        assert!(opts.is_err())
+    }
+
+    #[test]
+    fn time_style_without_long() {
+        let opts = Options::getopts(&[ "--time-style=iso".to_string() ]);
+        assert_eq!(opts.unwrap_err(), Misfire::Useless("time-style", false, "long"))
+    }
+
+    #[test]
+    fn sort_changed() {
+        let filter = Options::getopts(&[ "--sort=changed".to_string() ]).unwrap().0.filter;
+        assert_eq!(filter.sort_field, SortField::ChangedDate)
+    }
+
+    #[test]
+    fn total_size() {
+        let filter = Options::getopts(&[ "--long".to_string(), "--total-size".to_string(), "--sort=size".to_string() ]).unwrap().0.filter;
+        assert!(filter.total_size);
+        assert_eq!(filter.sort_field, SortField::Size)
+    }
+
+    #[test]
+    fn total_size_without_long() {
+        let opts = Options::getopts(&[ "--total-size".to_string() ]);
+        assert_eq!(opts.unwrap_err(), Misfire::Useless("total-size", false, "long"))
+    }
+
+    #[test]
+    fn total_size_with_format() {
+        let filter = Options::getopts(&[ "--format=json".to_string(), "--total-size".to_string() ]).unwrap().0.filter;
+        assert!(filter.total_size)
+    }
+
+    #[test]
+    #[cfg(feature="git")]
+    fn just_git_commit() {
+        let opts = Options::getopts(&[ "--git-commit".to_string() ]);
+        assert_eq!(opts.unwrap_err(), Misfire::Useless("git-commit", false, "long"))
+    }
+
+    #[test]
+    #[cfg(feature="git")]
+    fn just_git_repos() {
+        let opts = Options::getopts(&[ "--git-repos".to_string() ]);
+        assert_eq!(opts.unwrap_err(), Misfire::Useless("git-repos", false, "long"))
+    }
+
+    #[test]
+    #[cfg(feature="git")]
+    fn git_ref() {
+        let opts = Options::getopts(&[ "--git".to_string(), "--git-ref=HEAD".to_string() ]).unwrap().0;
+        assert_eq!(opts.git_ref, Some("HEAD".to_string()))
+    }
+
+    #[test]
+    #[cfg(feature="git")]
+    fn git_ref_missing() {
+        // A freshly-made repository has no commits, so not even HEAD.
+        let path = ::std::env::temp_dir().join("exa-test-git-ref-missing");
+        let _ = ::std::fs::remove_dir_all(&path);
+        ::git2::Repository::init(&path).unwrap();
+
+        let opts = Options::getopts(&[ "--git".to_string(), "--git-ref=HEAD".to_string(), path.to_string_lossy().into_owned() ]);
+        assert_eq!(opts.unwrap_err(), Misfire::BadGitRef("HEAD".to_string()));
+
+        ::std::fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature="git")]
+    fn git_ref_without_git() {
+        let opts = Options::getopts(&[ "--long".to_string(), "--git-ref=HEAD~5".to_string() ]);
+        assert_eq!(opts.unwrap_err(), Misfire::Useless("git-ref", false, "git"))
+    }
+
+    #[test]
+    fn bad_ignore_glob() {
+        let opts = Options::getopts(&[ "--ignore-glob=[abc".to_string() ]);
+        assert!(match opts.unwrap_err() { Misfire::FailedGlobPattern(ref p, _) => p == "[abc", _ => false })
+    }
+
     #[test]
     fn level_without_recurse_or_tree() {
         let opts = Options::getopts(&[ "--level".to_string(), "69105".to_string() ]);
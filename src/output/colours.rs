@@ -0,0 +1,560 @@
+use std::env;
+
+use ansi_term::Style;
+use ansi_term::Style::Plain;
+use ansi_term::Colour;
+use ansi_term::Colour::{Black, Red, Green, Yellow, Blue, Purple, Cyan, White, Fixed};
+
+/// This grey value is directly in between white and black, so it's guaranteed
+/// to show up on either backgrounded terminal.
+static GREY: Colour = Fixed(244);
+
+/// The **Colours** struct holds the style used for every part of exa's
+/// output that gets painted, from file names to permission bits.
+///
+/// The defaults can be changed using two environment variables. First,
+/// `LS_COLORS`, which is shared with `ls` and other tools, is read for the
+/// styles of file types and extensions. Then `EXA_COLORS`, which uses the
+/// same format, is read for those as well as the styles of everything else
+/// that exa paints. Both are lists of `key=value` pairs separated by colons,
+/// where each value is a list of ANSI SGR codes separated by semicolons,
+/// such as `di=1;34:*.log=38;5;244`. A `colours` line in the configuration
+/// file is read in between the two.
+#[derive(PartialEq, Debug, Clone)]
+pub struct Colours {
+    pub filetypes:   FileTypes,
+    pub perms:       Permissions,
+    pub size:        Size,
+    pub users:       Users,
+    pub links:       Links,
+    pub git:         Git,
+
+    pub punctuation:  Style,
+    pub date:         Style,
+    pub inode:        Style,
+    pub blocks:       Style,
+    pub header:       Style,
+    pub security_context: Style,
+
+    pub symlink_path:    Style,
+    pub broken_arrow:    Style,
+    pub broken_filename: Style,
+
+    /// Styles for files whose names end with a particular suffix, such as
+    /// `.tar.gz`, taken from the `*.suffix` keys. These take precedence over
+    /// exa's own idea of what type a regular file is.
+    pub suffixes: Vec<(String, Style)>,
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct FileTypes {
+    pub normal:     Style,
+    pub directory:  Style,
+    pub symlink:    Style,
+    pub pipe:       Style,
+    pub socket:     Style,
+    pub block_device: Style,
+    pub char_device:  Style,
+    pub special:    Style,
+    pub executable: Style,
+    pub image:      Style,
+    pub video:      Style,
+    pub music:      Style,
+    pub lossless:   Style,
+    pub crypto:     Style,
+    pub document:   Style,
+    pub compressed: Style,
+    pub temp:       Style,
+    pub immediate:  Style,
+    pub compiled:   Style,
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct Permissions {
+    pub user_read:          Style,
+    pub user_write:         Style,
+    pub user_execute_file:  Style,
+    pub user_execute_other: Style,
+
+    pub group_read:    Style,
+    pub group_write:   Style,
+    pub group_execute: Style,
+
+    pub other_read:    Style,
+    pub other_write:   Style,
+    pub other_execute: Style,
+
+    pub set_id: Style,
+    pub sticky: Style,
+
+    pub attribute: Style,
+    pub octal:     Style,
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct Size {
+    pub numbers: Style,
+    pub unit:    Style,
+    pub major:   Style,
+    pub minor:   Style,
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct Users {
+    pub user_you:           Style,
+    pub user_someone_else:  Style,
+    pub group_yours:        Style,
+    pub group_not_yours:    Style,
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct Links {
+    pub normal:          Style,
+    pub multi_link_file: Style,
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct Git {
+    pub new:        Style,
+    pub modified:   Style,
+    pub deleted:    Style,
+    pub renamed:    Style,
+    pub typechange: Style,
+    pub ignored:    Style,
+    pub conflicted: Style,
+    pub branch:     Style,
+    pub commit:     Style,
+}
+
+impl Colours {
+
+    /// The colours exa uses when none have been configured.
+    pub fn colourful() -> Colours {
+        Colours {
+            filetypes: FileTypes {
+                normal:      Plain,
+                directory:   Blue.bold(),
+                symlink:     Cyan.normal(),
+                pipe:        Yellow.normal(),
+                socket:      Red.bold(),
+                block_device: Yellow.bold(),
+                char_device:  Yellow.bold(),
+                special:     Yellow.normal(),
+                executable:  Green.bold(),
+                image:       Fixed(133).normal(),
+                video:       Fixed(135).normal(),
+                music:       Fixed(92).normal(),
+                lossless:    Fixed(93).normal(),
+                crypto:      Fixed(109).normal(),
+                document:    Fixed(105).normal(),
+                compressed:  Red.normal(),
+                temp:        GREY.normal(),
+                immediate:   Yellow.bold().underline(),
+                compiled:    Fixed(137).normal(),
+            },
+
+            perms: Permissions {
+                user_read:           Yellow.bold(),
+                user_write:          Red.bold(),
+                user_execute_file:   Green.bold().underline(),
+                user_execute_other:  Green.bold(),
+
+                group_read:          Yellow.normal(),
+                group_write:         Red.normal(),
+                group_execute:       Green.normal(),
+
+                other_read:          Yellow.normal(),
+                other_write:         Red.normal(),
+                other_execute:       Green.normal(),
+
+                set_id:              Purple.bold(),
+                sticky:              Purple.normal(),
+
+                attribute:           Plain,
+                octal:               Purple.normal(),
+            },
+
+            size: Size {
+                numbers:  Green.bold(),
+                unit:     Green.normal(),
+                major:    Green.bold(),
+                minor:    Green.normal(),
+            },
+
+            users: Users {
+                user_you:           Yellow.bold(),
+                user_someone_else:  Plain,
+                group_yours:        Yellow.bold(),
+                group_not_yours:    Plain,
+            },
+
+            links: Links {
+                normal:           Red.normal(),
+                multi_link_file:  Red.on(Yellow),
+            },
+
+            git: Git {
+                new:         Green.normal(),
+                modified:    Blue.normal(),
+                deleted:     Red.normal(),
+                renamed:     Yellow.normal(),
+                typechange:  Purple.normal(),
+                ignored:     GREY.normal(),
+                conflicted:  Red.bold(),
+                branch:      Fixed(208).normal(),
+                commit:      Yellow.normal(),
+            },
+
+            punctuation:  GREY.normal(),
+            date:         Blue.normal(),
+            inode:        Purple.normal(),
+            blocks:       Cyan.normal(),
+            header:       Plain.underline(),
+            security_context: Cyan.normal(),
+
+            symlink_path:     Cyan.normal(),
+            broken_arrow:     Red.normal(),
+            broken_filename:  Red.underline(),
+
+            suffixes: Vec::new(),
+        }
+    }
+
+    /// The default colours, with any changes from the `LS_COLORS`
+    /// variable, the configuration file, and the `EXA_COLORS` variable
+    /// applied on top, in that order.
+    pub fn deduce(config: Option<&str>) -> Colours {
+        let mut colours = Colours::colourful();
+
+        if let Ok(spec) = env::var("LS_COLORS") {
+            colours.apply(&spec, false);
+        }
+
+        if let Some(spec) = config {
+            colours.apply(spec, true);
+        }
+
+        if let Ok(spec) = env::var("EXA_COLORS") {
+            colours.apply(&spec, true);
+        }
+
+        colours
+    }
+
+    /// Apply a colon-separated list of `key=value` pairs on top of the
+    /// current styles. The keys only `ls` knows about are always read, but
+    /// exa's own keys are only read when `exa_keys` is true. Keys or values
+    /// that can't be understood are ignored, as `ls` does.
+    pub fn apply(&mut self, spec: &str, exa_keys: bool) {
+        for pair in spec.split(':') {
+            if exa_keys && pair == "reset" {
+                self.reset();
+                continue;
+            }
+
+            let mut parts = pair.splitn(2, '=');
+            let (key, value) = match (parts.next(), parts.next()) {
+                (Some(k), Some(v)) => (k, v),
+                _                  => continue,
+            };
+
+            let style = match parse_style(value) {
+                Some(s) => s,
+                None    => continue,
+            };
+
+            if key.starts_with("*") && key.len() > 1 {
+                self.suffixes.push((key[1..].to_string(), style));
+            }
+            else if !self.set_ls_key(key, style) && exa_keys {
+                self.set_exa_key(key, style);
+            }
+        }
+    }
+
+    /// Remove every colour, leaving everything unpainted. This is useful at
+    /// the start of `EXA_COLORS` for anyone who wants to pick every style
+    /// themselves.
+    fn reset(&mut self) {
+        *self = Colours::plain();
+    }
+
+    /// Set the style for one of the keys that `ls` uses. Returns whether the
+    /// key was recognised.
+    fn set_ls_key(&mut self, key: &str, style: Style) -> bool {
+        match key {
+            "fi" => self.filetypes.normal      = style,
+            "di" => self.filetypes.directory   = style,
+            "ln" => self.filetypes.symlink     = style,
+            "ex" => self.filetypes.executable  = style,
+            "pi" => self.filetypes.pipe        = style,
+            "so" => self.filetypes.socket      = style,
+            "bd" => self.filetypes.block_device = style,
+            "cd" => self.filetypes.char_device  = style,
+            "do" => self.filetypes.special     = style,
+            "or" => {
+                self.broken_arrow    = style;
+                self.broken_filename = style;
+            },
+            _    => return false,
+        }
+
+        true
+    }
+
+    /// Set the style for one of the keys that only exa uses.
+    fn set_exa_key(&mut self, key: &str, style: Style) {
+        match key {
+            "ur" => self.perms.user_read           = style,
+            "uw" => self.perms.user_write          = style,
+            "ux" => self.perms.user_execute_file   = style,
+            "ue" => self.perms.user_execute_other  = style,
+            "gr" => self.perms.group_read          = style,
+            "gw" => self.perms.group_write         = style,
+            "gx" => self.perms.group_execute       = style,
+            "tr" => self.perms.other_read          = style,
+            "tw" => self.perms.other_write         = style,
+            "tx" => self.perms.other_execute       = style,
+            "su" => self.perms.set_id              = style,
+            "st" => self.perms.sticky              = style,
+            "xa" => self.perms.attribute           = style,
+            "oc" => self.perms.octal               = style,
+
+            "sn" => self.size.numbers  = style,
+            "sb" => self.size.unit     = style,
+            "df" => self.size.major    = style,
+            "ds" => self.size.minor    = style,
+
+            "uu" => self.users.user_you           = style,
+            "un" => self.users.user_someone_else  = style,
+            "gu" => self.users.group_yours        = style,
+            "gn" => self.users.group_not_yours    = style,
+
+            "lc" => self.links.normal           = style,
+            "lm" => self.links.multi_link_file  = style,
+
+            "ga" => self.git.new         = style,
+            "gm" => self.git.modified    = style,
+            "gd" => self.git.deleted     = style,
+            "gv" => self.git.renamed     = style,
+            "gt" => self.git.typechange  = style,
+            "gi" => self.git.ignored     = style,
+            "gc" => self.git.conflicted  = style,
+            "gb" => self.git.branch      = style,
+            "gh" => self.git.commit      = style,
+
+            "xx" => self.punctuation  = style,
+            "da" => self.date         = style,
+            "in" => self.inode        = style,
+            "bl" => self.blocks       = style,
+            "hd" => self.header       = style,
+            "sc" => self.security_context = style,
+            "lp" => self.symlink_path = style,
+
+            "im" => self.filetypes.image       = style,
+            "vi" => self.filetypes.video       = style,
+            "mu" => self.filetypes.music       = style,
+            "lo" => self.filetypes.lossless    = style,
+            "cr" => self.filetypes.crypto      = style,
+            "dc" => self.filetypes.document    = style,
+            "co" => self.filetypes.compressed  = style,
+            "tm" => self.filetypes.temp        = style,
+            "bu" => self.filetypes.immediate   = style,
+            "cm" => self.filetypes.compiled    = style,
+
+            _    => {},
+        }
+    }
+
+    /// Every style set to Plain, with no suffixes.
+    pub fn plain() -> Colours {
+        Colours {
+            filetypes: FileTypes {
+                normal: Plain, directory: Plain, symlink: Plain, pipe: Plain,
+                socket: Plain, block_device: Plain, char_device: Plain, special: Plain,
+                executable: Plain, image: Plain, video: Plain, music: Plain,
+                lossless: Plain, crypto: Plain, document: Plain, compressed: Plain,
+                temp: Plain, immediate: Plain, compiled: Plain,
+            },
+
+            perms: Permissions {
+                user_read: Plain, user_write: Plain, user_execute_file: Plain, user_execute_other: Plain,
+                group_read: Plain, group_write: Plain, group_execute: Plain,
+                other_read: Plain, other_write: Plain, other_execute: Plain,
+                set_id: Plain, sticky: Plain,
+                attribute: Plain, octal: Plain,
+            },
+
+            size:  Size { numbers: Plain, unit: Plain, major: Plain, minor: Plain },
+            users: Users { user_you: Plain, user_someone_else: Plain, group_yours: Plain, group_not_yours: Plain },
+            links: Links { normal: Plain, multi_link_file: Plain },
+            git:   Git { new: Plain, modified: Plain, deleted: Plain, renamed: Plain, typechange: Plain,
+                           ignored: Plain, conflicted: Plain, branch: Plain, commit: Plain },
+
+            punctuation: Plain, date: Plain, inode: Plain, blocks: Plain, header: Plain, security_context: Plain,
+            symlink_path: Plain, broken_arrow: Plain, broken_filename: Plain,
+
+            suffixes: Vec::new(),
+        }
+    }
+
+    /// The style for a file with the given name from the `*.suffix` keys,
+    /// if any of them match. Later keys take precedence over earlier ones.
+    pub fn suffix_style(&self, name: &str) -> Option<Style> {
+        self.suffixes.iter().rev()
+                     .find(|&&(ref suffix, _)| name.ends_with(&suffix[..]))
+                     .map(|&(_, style)| style)
+    }
+}
+
+/// Turn a list of ANSI SGR codes separated by semicolons, such as `01;34`,
+/// into a Style. Returns None if any of the codes can't be understood.
+fn parse_style(codes: &str) -> Option<Style> {
+    let mut foreground = None;
+    let mut background = None;
+    let mut bold = false;
+    let mut underline = false;
+
+    let codes: Vec<&str> = codes.split(';').collect();
+    let mut i = 0;
+
+    while i < codes.len() {
+        let code: u8 = match codes[i].parse() {
+            Ok(c)  => c,
+            Err(_) => if codes[i].is_empty() { 0 } else { return None },
+        };
+
+        match code {
+            0        => { foreground = None; background = None; bold = false; underline = false },
+            1        => bold = true,
+            4        => underline = true,
+            30 ... 37 => foreground = Some(basic_colour(code - 30)),
+            40 ... 47 => background = Some(basic_colour(code - 40)),
+            90 ... 97 => foreground = Some(Fixed(code - 90 + 8)),
+            100 ... 107 => background = Some(Fixed(code - 100 + 8)),
+
+            // 256-colour codes are in the form 38;5;N or 48;5;N.
+            38 | 48 => {
+                if i + 2 >= codes.len() || codes[i + 1] != "5" {
+                    return None;
+                }
+
+                let colour = match codes[i + 2].parse() {
+                    Ok(n)  => Fixed(n),
+                    Err(_) => return None,
+                };
+
+                if code == 38 { foreground = Some(colour) } else { background = Some(colour) }
+                i += 2;
+            },
+
+            // Other attributes, such as blinking, aren't supported, but
+            // aren't worth rejecting the whole style over either.
+            _ => {},
+        }
+
+        i += 1;
+    }
+
+    let mut style = match foreground {
+        Some(c) => c.normal(),
+        None    => Plain,
+    };
+
+    if bold      { style = style.bold() }
+    if underline { style = style.underline() }
+
+    if let Some(c) = background {
+        style = style.on(c);
+    }
+
+    Some(style)
+}
+
+/// One of the eight basic terminal colours, by its number.
+fn basic_colour(number: u8) -> Colour {
+    match number {
+        0 => Black,
+        1 => Red,
+        2 => Green,
+        3 => Yellow,
+        4 => Blue,
+        5 => Purple,
+        6 => Cyan,
+        _ => White,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Colours, parse_style};
+
+    use ansi_term::Style::Plain;
+    use ansi_term::Colour::{Red, Blue, Fixed};
+
+    #[test]
+    fn bold_blue() {
+        assert_eq!(parse_style("01;34"), Some(Blue.bold()))
+    }
+
+    #[test]
+    fn fixed_colour() {
+        assert_eq!(parse_style("38;5;244"), Some(Fixed(244).normal()))
+    }
+
+    #[test]
+    fn reset_code() {
+        assert_eq!(parse_style("0"), Some(Plain))
+    }
+
+    #[test]
+    fn nonsense() {
+        assert_eq!(parse_style("blue"), None)
+    }
+
+    fn colours(ls_colors: &str, exa_colors: &str) -> Colours {
+        let mut colours = Colours::colourful();
+        colours.apply(ls_colors, false);
+        colours.apply(exa_colors, true);
+        colours
+    }
+
+    #[test]
+    fn ls_colors_directory() {
+        assert_eq!(colours("di=31", "").filetypes.directory, Red.normal())
+    }
+
+    #[test]
+    fn ls_colors_special_files() {
+        let colours = colours("pi=31:cd=34", "");
+        assert_eq!(colours.filetypes.pipe, Red.normal());
+        assert_eq!(colours.filetypes.char_device, Blue.normal());
+        assert_eq!(colours.filetypes.socket, Colours::colourful().filetypes.socket)
+    }
+
+    #[test]
+    fn ls_colors_ignores_exa_keys() {
+        assert_eq!(colours("da=31", "").date, Colours::colourful().date)
+    }
+
+    #[test]
+    fn exa_colors_override_ls_colors() {
+        let colours = colours("di=31", "di=34;1:da=31");
+        assert_eq!(colours.filetypes.directory, Blue.bold());
+        assert_eq!(colours.date, Red.normal())
+    }
+
+    #[test]
+    fn suffixes() {
+        let colours = colours("*.tar.gz=31:*.gz=34", "");
+        assert_eq!(colours.suffix_style("logs.tar.gz"), Some(Blue.normal()));
+        assert_eq!(colours.suffix_style("logs.txt"), None)
+    }
+
+    #[test]
+    fn reset() {
+        let colours = colours("", "reset:di=31");
+        assert_eq!(colours.filetypes.directory, Red.normal());
+        assert_eq!(colours.date, Plain)
+    }
+}
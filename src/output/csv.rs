@@ -0,0 +1,137 @@
+use std::os::unix::fs::MetadataExt;
+
+use column::Column;
+use file::{File, SizeCache};
+use options::{Columns, FileFilter, RecurseOptions};
+use output::Colours;
+
+/// The **CSV** view prints the same columns as the details view, but as
+/// comma- or tab-separated values with no colours or padding, so listings
+/// can be loaded into spreadsheets and databases.
+///
+/// Values are kept in their rawest form: sizes are in bytes, timestamps are
+/// in seconds since the Unix epoch, and users and groups are given as their
+/// numeric IDs. A header row with the names of the columns comes first.
+#[derive(PartialEq, Debug, Clone)]
+pub struct CSV {
+
+    /// The columns to print, in the same order as the details view.
+    pub columns: Columns,
+
+    /// The character to put between values: a comma or a tab.
+    pub separator: char,
+
+    /// Whether to recurse through directories with a tree view, and if so,
+    /// which options to use.
+    pub recurse: Option<(RecurseOptions, FileFilter)>,
+}
+
+impl CSV {
+
+    /// Print the header row, which goes before all of the files in the run.
+    pub fn start(&self) {
+        let mut headers: Vec<String> = self.columns.for_every_dir().iter()
+                                                   .map(|c| escape(c.header(), self.separator))
+                                                   .collect();
+        headers.push(escape("Name", self.separator));
+        headers.push(escape("Path", self.separator));
+
+        println!("{}", headers.connect(&self.separator.to_string()));
+    }
+
+    /// Print a row for each of the files.
+    pub fn view(&self, files: &[File], sizes: &SizeCache) {
+        let columns = self.columns.for_every_dir();
+        self.add_files(&columns, files, 0, sizes);
+    }
+
+    fn add_files(&self, columns: &[Column], files: &[File], depth: usize, sizes: &SizeCache) {
+        for file in files.iter() {
+            let mut values: Vec<String> = columns.iter()
+                                                 .map(|c| escape(&value(file, c), self.separator))
+                                                 .collect();
+            values.push(escape(&file.name, self.separator));
+            values.push(escape(&file.path.to_string_lossy(), self.separator));
+
+            println!("{}", values.connect(&self.separator.to_string()));
+
+            if let Some((r, ref filter)) = self.recurse {
+                if r.tree == false || r.is_too_deep(depth) {
+                    continue;
+                }
+
+                if let Some(ref dir) = file.this {
+                    let mut files = dir.files(true, filter);
+                    filter.transform_files(&mut files, sizes);
+                    self.add_files(columns, &files, depth + 1, sizes);
+                }
+            }
+        }
+    }
+}
+
+/// Quote a value if it contains the separator, a quote, or a newline,
+/// doubling any quotes inside it.
+fn escape(value: &str, separator: char) -> String {
+    if value.contains(separator) || value.contains('"') || value.contains('\n') || value.contains('\r') {
+        format!("\"{}\"", value.replace("\"", "\"\""))
+    }
+    else {
+        value.to_string()
+    }
+}
+
+/// The unpainted value of one of a file's columns.
+fn value(file: &File, column: &Column) -> String {
+    let raw = file.stat.as_raw();
+
+    match *column {
+        Column::Permissions     => permissions(file),
+        Column::Octal           => file.octal_permissions_string(),
+        Column::FileSize(_)     => file.size().to_string(),
+        Column::Timestamp(t, _, _) => file.timestamp_seconds(t).map(|s| s.to_string()).unwrap_or(String::new()),
+        Column::HardLinks       => raw.nlink().to_string(),
+        Column::Inode           => raw.ino().to_string(),
+        Column::Blocks          => file.block_count().to_string(),
+        Column::User            => raw.uid().to_string(),
+        Column::Group           => raw.gid().to_string(),
+        Column::InodeFlags      => file.inode_flags().map(|f| f.to_lsattr_string()).unwrap_or(String::new()),
+        Column::SecurityContext => file.security_context().unwrap_or(String::new()),
+        Column::GitStatus       => file.git_statuses().map(|s| s.to_plain_string()).unwrap_or(String::new()),
+        Column::GitCommitHash   => file.git_commit().map(|c| c.hash).unwrap_or(String::new()),
+        Column::GitCommitAuthor => file.git_commit().map(|c| c.author).unwrap_or(String::new()),
+        Column::GitCommitDate(_) => file.git_commit().map(|c| c.time.to_string()).unwrap_or(String::new()),
+        Column::GitRepo         => file.git_repo_cell(&Colours::plain()).text,
+    }
+}
+
+/// The "drwxr-xr-x" permissions string, without any colours, and without
+/// the extended attribute marker, which would only ever be a space here.
+fn permissions(file: &File) -> String {
+    file.permissions_string(&Colours::plain()).text.chars().take(10).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::escape;
+
+    #[test]
+    fn plain_value() {
+        assert_eq!(escape("Cargo.toml", ','), "Cargo.toml")
+    }
+
+    #[test]
+    fn comma() {
+        assert_eq!(escape("one, two", ','), "\"one, two\"")
+    }
+
+    #[test]
+    fn comma_in_tsv() {
+        assert_eq!(escape("one, two", '\t'), "one, two")
+    }
+
+    #[test]
+    fn quotes() {
+        assert_eq!(escape("say \"hi\"", ','), "\"say \"\"hi\"\"\"")
+    }
+}
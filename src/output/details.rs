@@ -1,12 +1,12 @@
 use column::{Alignment, Column, Cell};
-use feature::Attribute;
+use feature::{AclKind, Attribute, GitHead};
 use dir::Dir;
-use file::{File, GREY};
+use file::{File, SizeCache};
 use options::{Columns, FileFilter, RecurseOptions};
-use users::OSUsers;
+use output::{Colours, git_head_view};
+use users::{OSUsers, Users};
 
 use locale;
-use ansi_term::Style::Plain;
 
 /// With the **Details** view, the output gets formatted into columns, with
 /// each `Column` object showing some piece of information about the file,
@@ -19,7 +19,7 @@ use ansi_term::Style::Plain;
 ///
 /// Almost all the heavy lifting is done in a Table object, which handles the
 /// columns for each row.
-#[derive(PartialEq, Debug, Copy, Clone)]
+#[derive(PartialEq, Debug, Clone)]
 pub struct Details {
 
     /// A Columns object that says which columns should be included in the
@@ -37,23 +37,36 @@ pub struct Details {
 
     /// Whether to show each file's extended attributes.
     pub xattr: bool,
+
+    /// Whether to show the entries of each file's POSIX ACLs.
+    pub acl: bool,
 }
 
 impl Details {
-    pub fn view(&self, dir: Option<&Dir>, files: &[File]) {
+    pub fn view(&self, dir: Option<&Dir>, files: &[File], colours: &Colours, sizes: &SizeCache) {
         // First, transform the Columns object into a vector of columns for
         // the current directory.
-        let mut table = Table::with_columns(self.columns.for_dir(dir));
-        if self.header { table.add_header() }
+        let mut table = Table::with_columns(self.columns.for_dir(dir), colours);
+
+        // The header row shows where the repository's HEAD is when the
+        // Git column is being shown.
+        if self.header {
+            let head = match dir {
+                Some(d) if table.columns.contains(&Column::GitStatus) => d.git_head(),
+                _ => None,
+            };
+
+            table.add_header(head);
+        }
 
         // Then add files to the table and print it out.
-        self.add_files_to_table(&mut table, files, 0);
-        table.print_table(self.xattr, self.recurse.is_some());
+        self.add_files_to_table(&mut table, files, 0, sizes);
+        table.print_table(self.xattr, self.acl, self.recurse.is_some());
     }
 
     /// Adds files to the table - recursively, if the `recurse` option
     /// is present.
-    fn add_files_to_table(&self, table: &mut Table, src: &[File], depth: usize) {
+    fn add_files_to_table(&self, table: &mut Table, src: &[File], depth: usize, sizes: &SizeCache) {
         for (index, file) in src.iter().enumerate() {
             table.add_file(file, depth, index == src.len() - 1);
 
@@ -61,7 +74,7 @@ impl Details {
             // view, which is dealt with here, and multiple listings, which is
             // dealt with in the main module. So only actually recurse if we
             // are in tree mode - the other case will be dealt with elsewhere.
-            if let Some((r, filter)) = self.recurse {
+            if let Some((r, ref filter)) = self.recurse {
                 if r.tree == false || r.is_too_deep(depth) {
                     continue;
                 }
@@ -70,9 +83,9 @@ impl Details {
                 // them, so we don't examine any directories that wouldn't
                 // have their contents listed anyway.
                 if let Some(ref dir) = file.this {
-                    let mut files = dir.files(true);
-                    filter.transform_files(&mut files);
-                    self.add_files_to_table(table, &files, depth + 1);
+                    let mut files = dir.files(true, filter);
+                    filter.transform_files(&mut files, sizes);
+                    self.add_files_to_table(table, &files, depth + 1, sizes);
                 }
             }
         }
@@ -106,19 +119,21 @@ struct Row {
 
 /// A **Table** object gets built up by the view as it lists files and
 /// directories.
-struct Table {
+struct Table<'a> {
     columns: Vec<Column>,
+    colours: &'a Colours,
     users:   OSUsers,
     locale:  UserLocale,
     rows:    Vec<Row>,
 }
 
-impl Table {
+impl<'a> Table<'a> {
     /// Create a new, empty Table object, setting the caching fields to their
     /// empty states.
-    fn with_columns(columns: Vec<Column>) -> Table {
+    fn with_columns(columns: Vec<Column>, colours: &'a Colours) -> Table<'a> {
         Table {
             columns: columns,
+            colours: colours,
             users: OSUsers::empty_cache(),
             locale: UserLocale::new(),
             rows: Vec::new(),
@@ -128,11 +143,19 @@ impl Table {
     /// Add a dummy "header" row to the table, which contains the names of all
     /// the columns, underlined. This has dummy data for the cases that aren't
     /// actually used, such as the depth or list of attributes.
-    fn add_header(&mut self) {
+    ///
+    /// If a summary of the repository's HEAD is given, it goes after the
+    /// name column's header.
+    fn add_header(&mut self, head: Option<&GitHead>) {
+        let name = match head {
+            Some(h) => format!("{} {}", self.colours.header.paint("Name"), git_head_view(h, self.colours).text),
+            None    => self.colours.header.paint("Name").to_string(),
+        };
+
         let row = Row {
             depth:    0,
-            cells:    self.columns.iter().map(|c| Cell::paint(Plain.underline(), c.header())).collect(),
-            name:     Plain.underline().paint("Name").to_string(),
+            cells:    self.columns.iter().map(|c| Cell::paint(self.colours.header, c.header())).collect(),
+            name:     name,
             last:     false,
             attrs:    Vec::new(),
             children: false,
@@ -145,7 +168,7 @@ impl Table {
     /// this file, per-column.
     fn cells_for_file(&mut self, file: &File) -> Vec<Cell> {
         self.columns.clone().iter()
-                    .map(|c| file.display(c, &mut self.users, &self.locale))
+                    .map(|c| file.display(c, self.colours, &mut self.users, &self.locale))
                     .collect()
     }
 
@@ -154,7 +177,7 @@ impl Table {
         let row = Row {
             depth:    depth,
             cells:    self.cells_for_file(file),
-            name:     file.file_name_view(),
+            name:     file.file_name_view(self.colours),
             last:     last,
             attrs:    file.xattrs.clone(),
             children: file.this.is_some(),
@@ -164,7 +187,7 @@ impl Table {
     }
 
     /// Print the table to standard output, consuming it in the process.
-    fn print_table(self, xattr: bool, show_children: bool) {
+    fn print_table(mut self, xattr: bool, acl: bool, show_children: bool) {
         let mut stack = Vec::new();
 
         // Work out the list of column widths by finding the longest cell for
@@ -189,7 +212,7 @@ impl Table {
                 stack[row.depth] = if row.last { TreePart::Corner } else { TreePart::Edge };
 
                 for i in 1 .. row.depth + 1 {
-                    print!("{}", GREY.paint(stack[i].ascii_art()));
+                    print!("{}", self.colours.punctuation.paint(stack[i].ascii_art()));
                 }
 
                 if row.children {
@@ -210,12 +233,39 @@ impl Table {
                 let width = row.attrs.iter().map(|a| a.name().len()).max().unwrap_or(0);
                 for attr in row.attrs.iter() {
                     let name = attr.name();
-                    println!("{}\t{}",
+                    println!("{}\t{}\t{}",
                         Alignment::Left.pad_string(name, width - name.len()),
-                        attr.size()
+                        attr.size(),
+                        attr.describe_value()
                     )
                 }
             }
+
+            // ACL entries are shown one per line, like `getfacl` does, with
+            // the entries of a default ACL marked as such.
+            if acl {
+                for attr in row.attrs.iter() {
+                    let (kind, entries) = match (attr.acl_kind(), attr.acl_entries()) {
+                        (Some(k), Some(e)) => (k, e),
+                        _                  => continue,
+                    };
+
+                    let prefix = if kind == AclKind::Default { "default:" } else { "" };
+                    for entry in entries.iter() {
+                        let name = if entry.is_named_user() {
+                            self.users.get_user_by_uid(entry.id).map(|u| u.name)
+                        }
+                        else if entry.is_named_group() {
+                            self.users.get_group_by_gid(entry.id).map(|g| g.name)
+                        }
+                        else {
+                            None
+                        };
+
+                        println!("{}{}", prefix, entry.to_string_with_name(name.as_ref().map(|n| &n[..])));
+                    }
+                }
+            }
         }
     }
 }
@@ -0,0 +1,46 @@
+use ansi_term::{ANSIString, ANSIStrings, Style};
+use unicode_width::UnicodeWidthStr;
+
+use column::Cell;
+use feature::GitHead;
+use output::Colours;
+
+/// Paint a summary of where a repository's HEAD is, such as
+/// `[master, ahead 2, behind 1, dirty]`, for showing next to the names of
+/// directories inside the repository.
+pub fn git_head_view(head: &GitHead, colours: &Colours) -> Cell {
+    let name = if head.detached { format!("detached at {}", head.name) }
+                           else { head.name.clone() };
+
+    let mut parts: Vec<(Style, String)> = vec![ (colours.git.branch, name) ];
+
+    if let Some((ahead, behind)) = head.upstream {
+        if ahead > 0  { parts.push((colours.git.branch, format!("ahead {}", ahead))) }
+        if behind > 0 { parts.push((colours.git.branch, format!("behind {}", behind))) }
+    }
+
+    if head.dirty {
+        parts.push((colours.git.modified, "dirty".to_string()));
+    }
+
+    let mut strings: Vec<ANSIString> = vec![ colours.punctuation.paint("[") ];
+    let mut plain = "[".to_string();
+
+    for (i, &(style, ref text)) in parts.iter().enumerate() {
+        if i > 0 {
+            strings.push(colours.punctuation.paint(", "));
+            plain.push_str(", ");
+        }
+
+        strings.push(style.paint(text));
+        plain.push_str(text);
+    }
+
+    strings.push(colours.punctuation.paint("]"));
+    plain.push_str("]");
+
+    Cell {
+        text:   ANSIStrings(&strings).to_string(),
+        length: UnicodeWidthStr::width(&plain[..]),
+    }
+}
@@ -1,6 +1,7 @@
 use column::Alignment::Left;
 use file::File;
-use super::lines::lines_view;
+use output::Colours;
+use super::lines::Lines;
 
 use std::cmp::max;
 use std::iter::repeat;
@@ -9,21 +10,27 @@ use std::iter::repeat;
 pub struct Grid {
     pub across: bool,
     pub console_width: usize,
+
+    /// Whether to put each file's Git status before its name, which takes
+    /// up some extra room in each column.
+    pub git: bool,
 }
 
 impl Grid {
-    fn fit_into_grid(&self, files: &[File]) -> Option<(usize, Vec<usize>)> {
+    /// Find the fewest number of lines that entries with the given widths
+    /// fit into, along with the width of each column.
+    fn fit_into_grid(&self, entry_widths: &[usize]) -> Option<(usize, Vec<usize>)> {
         // TODO: this function could almost certainly be optimised...
         // surely not *all* of the numbers of lines are worth searching through!
 
         // Instead of numbers of columns, try to find the fewest number of *lines*
         // that the output will fit in.
-        for num_lines in 1 .. files.len() {
+        for num_lines in 1 .. entry_widths.len() {
 
             // The number of columns is the number of files divided by the number
             // of lines, *rounded up*.
-            let mut num_columns = files.len() / num_lines;
-            if files.len() % num_lines != 0 {
+            let mut num_columns = entry_widths.len() / num_lines;
+            if entry_widths.len() % num_lines != 0 {
                 num_columns += 1;
             }
 
@@ -44,14 +51,14 @@ impl Grid {
             // Find the width of each column by adding the lengths of the file
             // names in that column up.
             let mut column_widths: Vec<usize> = repeat(0).take(num_columns).collect();
-            for (index, file) in files.iter().enumerate() {
+            for (index, &width) in entry_widths.iter().enumerate() {
                 let index = if self.across {
                     index % num_columns
                 }
                 else {
                     index / num_lines
                 };
-                column_widths[index] = max(column_widths[index], file.file_name_width());
+                column_widths[index] = max(column_widths[index], width);
             }
 
             // If they all fit in the terminal, combined, then success!
@@ -64,8 +71,21 @@ impl Grid {
         return None;
     }
 
-    pub fn view(&self, files: &[File]) {
-        if let Some((num_lines, widths)) = self.fit_into_grid(files) {
+    /// The width of a file's entry in the grid: its name, along with its
+    /// Git status and a space if that's being shown.
+    fn entry_width(&self, file: &File) -> usize {
+        match file.git_statuses() {
+            Some(_) if self.git => file.file_name_width() + 3,
+            _                   => file.file_name_width(),
+        }
+    }
+
+    pub fn view(&self, files: &[File], colours: &Colours) {
+        // Each entry's width gets worked out once, up front, as it's needed
+        // again for every number of lines that gets tried.
+        let entry_widths: Vec<usize> = files.iter().map(|f| self.entry_width(f)).collect();
+
+        if let Some((num_lines, widths)) = self.fit_into_grid(&entry_widths) {
             for y in 0 .. num_lines {
                 for x in 0 .. widths.len() {
                     let num = if self.across {
@@ -81,14 +101,19 @@ impl Grid {
                     }
 
                     let ref file = files[num];
-                    let styled_name = file.file_colour().paint(&file.name).to_string();
+                    let mut styled_name = file.file_colour(colours).paint(&file.name).to_string();
+                    if let Some(marker) = file.git_status_marker(self.git, colours) {
+                        styled_name = format!("{} {}", marker.text, styled_name);
+                    }
+
+                    let width = entry_widths[num];
                     if x == widths.len() - 1 {
                         // The final column doesn't need to have trailing spaces
                         print!("{}", styled_name);
                     }
                     else {
-                        assert!(widths[x] >= file.file_name_width());
-                        print!("{}", Left.pad_string(&styled_name, widths[x] - file.file_name_width() + 2));
+                        assert!(widths[x] >= width);
+                        print!("{}", Left.pad_string(&styled_name, widths[x] - width + 2));
                     }
                 }
                 print!("\n");
@@ -96,7 +121,7 @@ impl Grid {
         }
         else {
             // Drop down to lines view if the file names are too big for a grid
-            lines_view(files);
+            Lines { git: self.git }.view(files, colours);
         }
     }
 }
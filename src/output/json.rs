@@ -0,0 +1,185 @@
+use std::cell::Cell;
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
+
+use file::{File, SizeCache};
+use options::{FileFilter, RecurseOptions};
+
+/// The **JSON** view prints out each file as an object of raw, unpainted
+/// values, for other programs to consume rather than people.
+///
+/// Every object has the same set of keys, whatever the other options:
+///
+/// - `name`, `path`: the file's name and its path, as strings;
+/// - `depth`: how many directories deep into a `--tree` listing it is;
+/// - `type`: one of `file`, `directory`, `symlink`, `pipe`, `socket`,
+///   `block_device`, `char_device`, or `special`;
+/// - `size`, `blocks`, `inode`, `links`, `uid`, `gid`: numbers from the
+///   file's stat information;
+/// - `permissions`: the permission bits of the file's mode, as a number;
+/// - `modified`, `accessed`, `changed`, `created`: timestamps, in seconds
+///   since the Unix epoch, with `created` being `null` if it isn't known;
+/// - `xattrs`: an array of objects with the `name` and `size` of each of the
+///   file's extended attributes;
+/// - `git`: the two-character Git status, such as `"-M"`, or `null` if Git
+///   statuses weren't asked for or the file isn't in a repository.
+///
+/// With `--format=json`, every object in the run goes into one big array.
+/// With `--format=ndjson`, each object is printed on a line of its own.
+#[derive(PartialEq, Debug, Clone)]
+pub struct JSON {
+
+    /// Whether to print one object per line, rather than one array.
+    pub newline_delimited: bool,
+
+    /// Whether to recurse through directories with a tree view, and if so,
+    /// which options to use. As with the details view, this is only
+    /// relevant here if the `tree` field of the RecurseOptions is `true`.
+    pub recurse: Option<(RecurseOptions, FileFilter)>,
+
+    /// Whether to include each file's Git status.
+    pub git: bool,
+}
+
+impl JSON {
+
+    /// Print the text that goes before any of the objects.
+    pub fn start(&self) {
+        if !self.newline_delimited {
+            print!("[");
+        }
+    }
+
+    /// Print the text that goes after all of the objects.
+    pub fn end(&self, printed_any: bool) {
+        if !self.newline_delimited {
+            if printed_any { print!("\n") }
+            print!("]\n");
+        }
+    }
+
+    /// Print an object for each of the files. The `printed_any` flag is
+    /// shared between every call in the run, so that a separator only gets
+    /// printed *between* objects in an array.
+    pub fn view(&self, files: &[File], printed_any: &Cell<bool>, sizes: &SizeCache) {
+        self.add_files(files, 0, printed_any, sizes);
+    }
+
+    fn add_files(&self, files: &[File], depth: usize, printed_any: &Cell<bool>, sizes: &SizeCache) {
+        for file in files.iter() {
+            let object = self.object(file, depth);
+
+            if self.newline_delimited {
+                println!("{}", object);
+            }
+            else if printed_any.get() {
+                print!(",\n  {}", object);
+            }
+            else {
+                print!("\n  {}", object);
+            }
+
+            printed_any.set(true);
+
+            if let Some((r, ref filter)) = self.recurse {
+                if r.tree == false || r.is_too_deep(depth) {
+                    continue;
+                }
+
+                if let Some(ref dir) = file.this {
+                    let mut files = dir.files(true, filter);
+                    filter.transform_files(&mut files, sizes);
+                    self.add_files(&files, depth + 1, printed_any, sizes);
+                }
+            }
+        }
+    }
+
+    /// Build the JSON object for one file.
+    fn object(&self, file: &File, depth: usize) -> String {
+        let raw = file.stat.as_raw();
+
+        let xattrs: Vec<String> = file.xattrs.iter()
+            .map(|a| format!("{{\"name\":{},\"size\":{}}}", escape(a.name()), a.size()))
+            .collect();
+
+        let git = match file.git_statuses() {
+            Some(ref s) if self.git => escape(&s.to_plain_string()),
+            _                       => "null".to_string(),
+        };
+
+        let mut fields = Vec::new();
+        fields.push(format!("\"name\":{}",        escape(&file.name)));
+        fields.push(format!("\"path\":{}",        escape(&file.path.to_string_lossy())));
+        fields.push(format!("\"depth\":{}",       depth));
+        fields.push(format!("\"type\":\"{}\"",    type_name(file)));
+        fields.push(format!("\"size\":{}",        file.size()));
+        fields.push(format!("\"blocks\":{}",      file.block_count()));
+        fields.push(format!("\"inode\":{}",       raw.ino()));
+        fields.push(format!("\"links\":{}",       raw.nlink()));
+        fields.push(format!("\"uid\":{}",         raw.uid()));
+        fields.push(format!("\"gid\":{}",         raw.gid()));
+        fields.push(format!("\"permissions\":{}", file.stat.permissions().mode() & 0o7777));
+        fields.push(format!("\"modified\":{}",    raw.mtime()));
+        fields.push(format!("\"accessed\":{}",    raw.atime()));
+        fields.push(format!("\"changed\":{}",     raw.ctime()));
+        fields.push(format!("\"created\":{}",     file.birth_time().map(|t| t.to_string()).unwrap_or("null".to_string())));
+        fields.push(format!("\"xattrs\":[{}]",    xattrs.connect(",")));
+        fields.push(format!("\"git\":{}",         git));
+
+        format!("{{{}}}", fields.connect(","))
+    }
+}
+
+/// The word used for the type of this file in the `type` field.
+fn type_name(file: &File) -> &'static str {
+    if file.is_file()              { "file" }
+    else if file.is_directory()    { "directory" }
+    else if file.is_link()         { "symlink" }
+    else if file.is_pipe()         { "pipe" }
+    else if file.is_socket()       { "socket" }
+    else if file.is_block_device() { "block_device" }
+    else if file.is_char_device()  { "char_device" }
+    else                           { "special" }
+}
+
+/// Surround a string with quotes, escaping any characters that can't appear
+/// inside a JSON string as they are.
+fn escape(input: &str) -> String {
+    let mut output = String::with_capacity(input.len() + 2);
+    output.push('"');
+
+    for c in input.chars() {
+        match c {
+            '"'  => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            c if (c as u32) < 0x20 => output.push_str(&format!("\\u{:04x}", c as u32)),
+            c    => output.push(c),
+        }
+    }
+
+    output.push('"');
+    output
+}
+
+#[cfg(test)]
+mod test {
+    use super::escape;
+
+    #[test]
+    fn plain() {
+        assert_eq!(escape("Cargo.toml"), "\"Cargo.toml\"")
+    }
+
+    #[test]
+    fn quotes_and_backslashes() {
+        assert_eq!(escape("a \"b\" \\c"), "\"a \\\"b\\\" \\\\c\"")
+    }
+
+    #[test]
+    fn control_characters() {
+        assert_eq!(escape("new\nline\x01"), "\"new\\nline\\u0001\"")
+    }
+}
@@ -1,8 +1,21 @@
 use file::File;
+use output::Colours;
 
 /// The lines view literally just displays each file, line-by-line.
-pub fn lines_view(files: &[File]) {
-    for file in files {
-        println!("{}", file.file_name_view());
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub struct Lines {
+
+    /// Whether to put each file's Git status before its name.
+    pub git: bool,
+}
+
+impl Lines {
+    pub fn view(&self, files: &[File], colours: &Colours) {
+        for file in files {
+            match file.git_status_marker(self.git, colours) {
+                Some(marker) => println!("{} {}", marker.text, file.file_name_view(colours)),
+                None         => println!("{}", file.file_name_view(colours)),
+            }
+        }
     }
 }
@@ -1,7 +1,15 @@
+pub mod colours;
+mod csv;
 mod grid;
 pub mod details;
+mod git_head;
+mod json;
 mod lines;
 
+pub use self::colours::Colours;
+pub use self::csv::CSV;
 pub use self::grid::Grid;
 pub use self::details::Details;
-pub use self::lines::lines_view;
+pub use self::git_head::git_head_view;
+pub use self::json::JSON;
+pub use self::lines::Lines;
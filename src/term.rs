@@ -28,6 +28,7 @@ mod c {
 
     extern {
         pub fn ioctl(fd: c_int, request: c_ulong, ...) -> c_int;
+        pub fn isatty(fd: c_int) -> c_int;
     }
 
     pub unsafe fn dimensions() -> winsize {
@@ -49,3 +50,9 @@ pub fn dimensions() -> Option<(usize, usize)> {
         Some((w.ws_col as usize, w.ws_row as usize))
     }
 }
+
+/// Whether the current process's output is going to a terminal, rather than
+/// to a pipe or a file.
+pub fn stdout_is_terminal() -> bool {
+    unsafe { c::isatty(c::STDOUT_FILENO) != 0 }
+}
@@ -0,0 +1,115 @@
+use std::ffi::CString;
+use std::mem::zeroed;
+use std::ptr;
+
+mod c {
+    #![allow(non_camel_case_types)]
+    extern crate libc;
+    pub use self::libc::{
+        c_char,
+        c_int,
+        c_long,
+        size_t,
+        time_t,
+    };
+
+    // The broken-down time that localtime_r fills in. The last two fields
+    // aren't in the C standard, but both Linux and OS X have them.
+
+    #[repr(C)]
+    pub struct tm {
+        pub tm_sec:    c_int,
+        pub tm_min:    c_int,
+        pub tm_hour:   c_int,
+        pub tm_mday:   c_int,
+        pub tm_mon:    c_int,
+        pub tm_year:   c_int,
+        pub tm_wday:   c_int,
+        pub tm_yday:   c_int,
+        pub tm_isdst:  c_int,
+        pub tm_gmtoff: c_long,
+        pub tm_zone:   *const c_char,
+    }
+
+    extern {
+        pub fn time(t: *mut time_t) -> time_t;
+        pub fn localtime_r(time: *const time_t, result: *mut tm) -> *mut tm;
+        pub fn strftime(s: *mut c_char, max: size_t, format: *const c_char, tm: *const tm) -> size_t;
+    }
+}
+
+/// The current time, in seconds since the Unix epoch, for working out how
+/// long ago files were changed and commits were made.
+pub fn seconds_now() -> i64 {
+    unsafe { c::time(ptr::null_mut()) as i64 }
+}
+
+/// Format a timestamp in the local timezone, using a `strftime` format
+/// string. As well as the usual conversions, `%N` gets replaced with the
+/// nanoseconds, padded to nine digits, like how `date` does it. Returns an
+/// empty string if the time can't be formatted.
+pub fn format_local(format: &str, seconds: i64, nanoseconds: i64) -> String {
+    let format = match CString::new(expand_nanoseconds(format, nanoseconds)) {
+        Ok(f)  => f,
+        Err(_) => return String::new(),
+    };
+
+    let time = seconds as c::time_t;
+    let mut tm: c::tm = unsafe { zeroed() };
+    if unsafe { c::localtime_r(&time, &mut tm) }.is_null() {
+        return String::new();
+    }
+
+    // There's no way to ask strftime how much room it needs, so give it
+    // plenty: most conversions are only a few characters long.
+    let mut buf = vec![0u8; format.as_bytes().len() * 16 + 64];
+    let length = unsafe {
+        c::strftime(buf.as_mut_ptr() as *mut c::c_char, buf.len() as c::size_t, format.as_ptr(), &tm)
+    };
+
+    buf.truncate(length as usize);
+    String::from_utf8_lossy(&buf).into_owned()
+}
+
+/// Replace each `%N` in a format string with the given nanoseconds, leaving
+/// any other conversions, including `%%`, for strftime to deal with.
+fn expand_nanoseconds(format: &str, nanoseconds: i64) -> String {
+    let mut result = String::new();
+    let mut chars = format.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            result.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('N') => result.push_str(&format!("{:09}", nanoseconds)),
+            Some(n)   => { result.push('%'); result.push(n); },
+            None      => result.push('%'),
+        }
+    }
+
+    result
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::expand_nanoseconds;
+
+    #[test]
+    fn nanoseconds() {
+        assert_eq!(expand_nanoseconds("%S.%N", 1234), "%S.000001234")
+    }
+
+    #[test]
+    fn escaped_percent() {
+        assert_eq!(expand_nanoseconds("100%%N", 5), "100%%N")
+    }
+
+    #[test]
+    fn trailing_percent() {
+        assert_eq!(expand_nanoseconds("%N%", 0), "000000000%")
+    }
+}